@@ -15,14 +15,16 @@
 //! Facade and handler for the rest of the blockchain implementation
 //! and mostly the chain pipeline.
 
+use crate::core::consensus::HeaderInfo;
 use crate::core::core::hash::{Hash, Hashed, ZERO_HASH};
 use crate::core::core::merkle_proof::MerkleProof;
 use crate::core::core::verifier_cache::VerifierCache;
 use crate::core::core::{
-	Block, BlockHeader, BlockSums, Committed, Output, OutputIdentifier, Transaction, TxKernel,
+	block, pmmr, Block, BlockHeader, BlockSums, Committed, Output, OutputIdentifier, Transaction,
+	TxKernel,
 };
 use crate::core::global;
-use crate::core::pow;
+use crate::core::pow::{self, Difficulty};
 use crate::core::ser::{ProtocolVersion, Readable, StreamingReader};
 use crate::error::{Error, ErrorKind};
 use crate::pipe;
@@ -35,7 +37,7 @@ use crate::types::{
 use crate::util::secp::pedersen::{Commitment, RangeProof};
 use crate::util::RwLock;
 use kepler_store::Error::NotFoundErr;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::Read;
 use std::path::PathBuf;
@@ -275,6 +277,15 @@ impl Chain {
 		res
 	}
 
+	/// Processes a block like `process_block` but always returns the tip of
+	/// the branch the block was accepted on (including its total difficulty),
+	/// whether or not it became the new chain head.
+	pub fn accept_block(&self, b: Block, opts: Options) -> Result<Tip, Error> {
+		let tip = Tip::from_header(&b.header);
+		self.process_block(b, opts)?;
+		Ok(tip)
+	}
+
 	fn determine_status(&self, head: Option<Tip>, prev_head: Tip) -> BlockStatus {
 		// We have more work if the chain head is updated.
 		let is_more_work = head.is_some();
@@ -416,6 +427,18 @@ impl Chain {
 		Ok(())
 	}
 
+	/// Check a block building on the current head would be accepted, running
+	/// the full validation pipeline (pow, header, body, coinbase maturity,
+	/// utxo, kernel sums and txhashset roots) without changing chain state.
+	/// Useful to pre-check candidate blocks, e.g. mining pool submissions.
+	pub fn validate_block_against_tip(&self, b: &Block) -> Result<(), Error> {
+		let mut header_pmmr = self.header_pmmr.write();
+		let mut txhashset = self.txhashset.write();
+		let batch = self.store.batch()?;
+		let mut ctx = self.new_ctx(Options::NONE, batch, &mut header_pmmr, &mut txhashset)?;
+		pipe::validate_block_against_tip(b, &mut ctx)
+	}
+
 	fn new_ctx<'a>(
 		&self,
 		opts: Options,
@@ -527,6 +550,44 @@ impl Chain {
 		})
 	}
 
+	/// Validate a batch of txs against a single snapshot of the current UTXO set,
+	/// returning a result per tx (in the order provided).
+	/// A tx spending an output already spent, or creating an output already
+	/// created, by an earlier tx in the batch is rejected as a conflict.
+	pub fn validate_tx_batch(&self, txs: &[Transaction]) -> Vec<Result<(), Error>> {
+		let header_pmmr = self.header_pmmr.read();
+		let txhashset = self.txhashset.read();
+		let res = txhashset::utxo_view(&header_pmmr, &txhashset, |utxo, batch| {
+			let mut spent = HashSet::new();
+			let mut created = HashSet::new();
+			Ok(txs
+				.iter()
+				.map(|tx| -> Result<(), Error> {
+					utxo.validate_tx(tx, batch)?;
+					if let Some(input) =
+						tx.inputs().iter().find(|x| spent.contains(&x.commitment()))
+					{
+						return Err(ErrorKind::AlreadySpent(input.commitment()).into());
+					}
+					if let Some(output) = tx
+						.outputs()
+						.iter()
+						.find(|x| created.contains(&x.commitment()))
+					{
+						return Err(ErrorKind::DuplicateCommitment(output.commitment()).into());
+					}
+					spent.extend(tx.inputs().iter().map(|x| x.commitment()));
+					created.extend(tx.outputs().iter().map(|x| x.commitment()));
+					Ok(())
+				})
+				.collect::<Vec<_>>())
+		});
+		match res {
+			Ok(results) => results,
+			Err(e) => txs.iter().map(|_| Err(e.kind().into())).collect(),
+		}
+	}
+
 	fn next_block_height(&self) -> Result<u64, Error> {
 		let bh = self.head_header()?;
 		Ok(bh.height + 1)
@@ -544,6 +605,25 @@ impl Chain {
 		})
 	}
 
+	/// The height at which the provided unspent coinbase output becomes
+	/// spendable. None for plain outputs, which are spendable immediately, and
+	/// for outputs not in the current UTXO set (unknown or already spent).
+	pub fn output_maturity_height(&self, out: &OutputIdentifier) -> Result<Option<u64>, Error> {
+		match self.is_unspent(out) {
+			Ok(pos) => {
+				if out.features.is_coinbase() {
+					Ok(Some(pos.height + global::coinbase_maturity()))
+				} else {
+					Ok(None)
+				}
+			}
+			Err(e) => match e.kind() {
+				ErrorKind::OutputNotFound => Ok(None),
+				_ => Err(e),
+			},
+		}
+	}
+
 	/// Verify that the tx has a lock_height that is less than or equal to
 	/// the height of the next block.
 	pub fn verify_tx_lock_height(&self, tx: &Transaction) -> Result<(), Error> {
@@ -556,6 +636,9 @@ impl Chain {
 	}
 
 	/// Validate the current chain state.
+	/// Full (non-fast) validation also revalidates every block we still have
+	/// on the main chain, reporting the height of the first invalid block
+	/// via `ErrorKind::InvalidBlockAtHeight`.
 	pub fn validate(&self, fast_validation: bool) -> Result<(), Error> {
 		let header = self.store.head_header()?;
 
@@ -564,6 +647,10 @@ impl Chain {
 			return Ok(());
 		}
 
+		if !fast_validation {
+			self.validate_blocks(&header)?;
+		}
+
 		let mut header_pmmr = self.header_pmmr.write();
 		let mut txhashset = self.txhashset.write();
 
@@ -578,6 +665,33 @@ impl Chain {
 		})
 	}
 
+	/// Revalidate each block on the main chain up to the provided header.
+	/// Blocks removed from the db by compaction are skipped.
+	fn validate_blocks(&self, head: &BlockHeader) -> Result<(), Error> {
+		let header_pmmr = self.header_pmmr.read();
+		let mut prev = self.genesis.clone();
+		for height in 1..=head.height {
+			let hash = header_pmmr.get_header_hash_by_height(height)?;
+			let header = self.get_block_header(&hash)?;
+			if self.block_exists(hash)? {
+				let block = self.get_block(&hash)?;
+				let res = if block.hash() != hash {
+					Err(block::Error::Other("block hash mismatch".to_string()))
+				} else {
+					block
+						.validate(&prev.total_kernel_offset, self.verifier_cache.clone())
+						.map(|_| ())
+				};
+				if let Err(e) = res {
+					error!("validate: invalid block {} at {}: {:?}", hash, height, e);
+					return Err(ErrorKind::InvalidBlockAtHeight(height, e).into());
+				}
+			}
+			prev = header;
+		}
+		Ok(())
+	}
+
 	/// Sets the txhashset roots on a brand new block by applying the block on
 	/// the current txhashset state.
 	pub fn set_txhashset_roots(&self, b: &mut Block) -> Result<(), Error> {
@@ -1279,6 +1393,39 @@ impl Chain {
 		self.get_block_header(&hash)
 	}
 
+	/// Gets the full block at the provided height on the current chain.
+	/// Verifies the block we load is actually at the requested height, a
+	/// mismatch indicates a corrupted db.
+	/// Note: Takes a read lock on the header_pmmr.
+	pub fn get_block_by_height(&self, height: u64) -> Result<Block, Error> {
+		let hash = self.get_header_hash_by_height(height)?;
+		let block = self.get_block(&hash)?;
+		if block.header.height != height {
+			return Err(ErrorKind::Other(format!(
+				"block {} at height {}, expected height {}",
+				hash, block.header.height, height
+			))
+			.into());
+		}
+		Ok(block)
+	}
+
+	/// Iterates over the full blocks on the current chain in height order,
+	/// from start_height up to the chain head (as of this call).
+	/// Blocks are loaded lazily, one at a time, as the iterator is advanced.
+	pub fn blocks_from(
+		&self,
+		start_height: u64,
+	) -> impl Iterator<Item = Result<Block, Error>> + '_ {
+		let (heights, head_err) = match self.head() {
+			Ok(head) => (start_height..head.height + 1, None),
+			Err(e) => (0..0, Some(Err(e))),
+		};
+		head_err
+			.into_iter()
+			.chain(heights.map(move |height| self.get_block_by_height(height)))
+	}
+
 	/// Gets the header hash at the provided height.
 	/// Note: Takes a read lock on the header_pmmr.
 	fn get_header_hash_by_height(&self, height: u64) -> Result<Hash, Error> {
@@ -1389,6 +1536,21 @@ impl Chain {
 		}
 	}
 
+	/// Gets a block header by hash along with whether it is on the current
+	/// header chain, checked against the header MMR at the header height.
+	/// Headers on forks (or orphans), including those beyond the current
+	/// header head, are returned with false.
+	/// Note: Takes a read lock on the header_pmmr.
+	pub fn header_at_hash(&self, h: &Hash) -> Result<(BlockHeader, bool), Error> {
+		let header = self.get_block_header(h)?;
+		let header_pmmr = self.header_pmmr.read();
+		if header.height >= pmmr::n_leaves(header_pmmr.last_pos) {
+			return Ok((header, false));
+		}
+		let on_main_chain = header_pmmr.get_header_hash_by_height(header.height)? == *h;
+		Ok((header, on_main_chain))
+	}
+
 	/// Get the tip of the current "sync" header chain.
 	/// This may be significantly different to current header chain.
 	pub fn get_sync_head(&self) -> Result<Tip, Error> {
@@ -1406,6 +1568,36 @@ impl Chain {
 		Ok(store::DifficultyIter::from(head.last_block_h, store))
 	}
 
+	/// Headers used to retarget difficulty for the block at the provided height
+	/// on the current chain, starting from its parent and running backward.
+	/// Built from the header MMR entries so no full headers are loaded.
+	/// Returns at most `window` entries, fewer when close to genesis (unlike
+	/// `consensus::next_difficulty` this does not pad the window).
+	pub fn difficulty_window(&self, height: u64, window: u64) -> Result<Vec<HeaderInfo>, Error> {
+		if height == 0 {
+			return Err(ErrorKind::Other("no difficulty window for genesis".to_owned()).into());
+		}
+		// Each entry's difficulty is the delta in total difficulty from its
+		// parent, so we also need the entry just before the window (if any).
+		let header_pmmr = self.header_pmmr.read();
+		let from = height.saturating_sub(window);
+		let mut prev_total_difficulty = if from == 0 {
+			Difficulty::zero()
+		} else {
+			header_pmmr
+				.get_header_entry_by_height(from - 1)?
+				.total_difficulty()
+		};
+		let mut infos = Vec::with_capacity((height - from) as usize);
+		for h in from..height {
+			let entry = header_pmmr.get_header_entry_by_height(h)?;
+			infos.push(entry.header_info(prev_total_difficulty));
+			prev_total_difficulty = entry.total_difficulty();
+		}
+		infos.reverse();
+		Ok(infos)
+	}
+
 	/// Check whether we have a block without reading it
 	pub fn block_exists(&self, h: Hash) -> Result<bool, Error> {
 		self.store
@@ -59,6 +59,9 @@ pub enum ErrorKind {
 	/// The block doesn't sum correctly or a tx signature is invalid
 	#[fail(display = "Invalid Block Proof")]
 	InvalidBlockProof(block::Error),
+	/// A stored block on the main chain failed full revalidation
+	#[fail(display = "Invalid Block at height {}: {:?}", _0, _1)]
+	InvalidBlockAtHeight(u64, block::Error),
 	/// Block time is too old
 	#[fail(display = "Invalid Block Time")]
 	InvalidBlockTime,
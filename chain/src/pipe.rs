@@ -176,6 +176,32 @@ pub fn process_block(b: &Block, ctx: &mut BlockContext<'_>) -> Result<Option<Tip
 	}
 }
 
+/// Runs the full block validation pipeline for a block building on the
+/// current chain head without accepting it. All txhashset changes are
+/// discarded and nothing is written to the db (the batch is never committed).
+pub fn validate_block_against_tip(b: &Block, ctx: &mut BlockContext<'_>) -> Result<(), Error> {
+	let head = ctx.batch.head()?;
+	if b.header.prev_hash != head.last_block_h {
+		return Err(ErrorKind::Other("block does not build on the current head".to_owned()).into());
+	}
+
+	validate_header(&b.header, ctx)?;
+	validate_block(b, ctx)?;
+
+	let prev = prev_header_store(&b.header, &mut ctx.batch)?;
+	let ref mut header_pmmr = &mut ctx.header_pmmr;
+	let ref mut txhashset = &mut ctx.txhashset;
+	txhashset::extending_readonly(header_pmmr, txhashset, |ext, batch| {
+		rewind_and_apply_fork(&prev, ext, batch)?;
+		ext.header_extension.validate_root(&b.header)?;
+		verify_coinbase_maturity(b, ext, batch)?;
+		validate_utxo(b, ext, batch)?;
+		verify_block_sums(b, batch)?;
+		apply_block_to_txhashset(b, ext, batch)?;
+		Ok(())
+	})
+}
+
 /// Sync a chunk of block headers.
 /// This is only used during header sync.
 pub fn sync_block_headers(
@@ -327,10 +353,18 @@ fn validate_header(header: &BlockHeader, ctx: &mut BlockContext<'_>) -> Result<(
 		return Err(ErrorKind::InvalidBlockVersion(header.version).into());
 	}
 
-	if header.timestamp <= prev.timestamp {
-		// prevent time warp attacks and some timestamp manipulations by forcing strict
-		// time progression
-		return Err(ErrorKind::InvalidBlockTime.into());
+	// prevent time warp attacks and some timestamp manipulations by forcing strict
+	// time progression (and refusing headers too far in the future)
+	header
+		.verify_timestamp(&prev)
+		.map_err(|_| ErrorKind::InvalidBlockTime)?;
+
+	// The output and kernel MMRs are append-only so their sizes can never
+	// decrease relative to the previous header.
+	if header.output_mmr_size < prev.output_mmr_size
+		|| header.kernel_mmr_size < prev.kernel_mmr_size
+	{
+		return Err(ErrorKind::InvalidMMRSize.into());
 	}
 
 	// verify the proof of work and related parameters
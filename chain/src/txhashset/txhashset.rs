@@ -19,7 +19,9 @@ use crate::core::core::committed::Committed;
 use crate::core::core::hash::{Hash, Hashed};
 use crate::core::core::merkle_proof::MerkleProof;
 use crate::core::core::pmmr::{self, Backend, ReadonlyPMMR, RewindablePMMR, PMMR};
-use crate::core::core::{Block, BlockHeader, Input, Output, OutputIdentifier, TxKernel};
+use crate::core::core::{
+	Block, BlockHeader, HeaderEntry, Input, Output, OutputIdentifier, TxKernel,
+};
 use crate::core::ser::{PMMRIndexHashable, PMMRable, ProtocolVersion};
 use crate::error::{Error, ErrorKind};
 use crate::store::{Batch, ChainStore};
@@ -78,10 +80,17 @@ impl<T: PMMRable> PMMRHandle<T> {
 impl PMMRHandle<BlockHeader> {
 	/// Get the header hash at the specified height based on the current header MMR state.
 	pub fn get_header_hash_by_height(&self, height: u64) -> Result<Hash, Error> {
+		let entry = self.get_header_entry_by_height(height)?;
+		Ok(entry.hash())
+	}
+
+	/// Get the header MMR entry (hash, timestamp and difficulty data) at the
+	/// specified height based on the current header MMR state.
+	pub fn get_header_entry_by_height(&self, height: u64) -> Result<HeaderEntry, Error> {
 		let pos = pmmr::insertion_to_pmmr_index(height + 1);
 		let header_pmmr = ReadonlyPMMR::at(&self.backend, self.last_pos);
 		if let Some(entry) = header_pmmr.get_data(pos) {
-			Ok(entry.hash())
+			Ok(entry)
 		} else {
 			Err(ErrorKind::Other("get header hash by height".to_string()).into())
 		}
@@ -14,9 +14,11 @@
 
 use self::chain::types::{NoopAdapter, Tip};
 use self::chain::Chain;
-use self::core::core::hash::Hashed;
+use self::core::core::hash::{Hash, Hashed};
 use self::core::core::verifier_cache::LruVerifierCache;
-use self::core::core::{Block, BlockHeader, KernelFeatures, OutputIdentifier, Transaction};
+use self::core::core::{
+	Block, BlockHeader, KernelFeatures, OutputFeatures, OutputIdentifier, Transaction,
+};
 use self::core::global::ChainTypes;
 use self::core::libtx::{self, build, ProofBuilder};
 use self::core::pow::Difficulty;
@@ -25,7 +27,7 @@ use self::keychain::{ExtKeychain, ExtKeychainPath, Keychain};
 use self::util::RwLock;
 use chrono::Duration;
 use kepler_chain as chain;
-use kepler_chain::{BlockStatus, ChainAdapter, Options};
+use kepler_chain::{BlockStatus, ChainAdapter, ErrorKind, Options};
 use kepler_core as core;
 use kepler_keychain as keychain;
 use kepler_util as util;
@@ -826,6 +828,334 @@ fn output_header_mappings() {
 	clean_output_dir(".kepler_header_for_output");
 }
 
+/// Test retrieving full blocks by height
+#[test]
+fn get_block_by_height() {
+	let chain_dir = ".kepler.block_by_height";
+	clean_output_dir(chain_dir);
+	{
+		let chain = mine_chain(chain_dir, 5);
+		for height in 0..5 {
+			let header = chain.get_header_by_height(height).unwrap();
+			let block = chain.get_block_by_height(height).unwrap();
+			assert_eq!(block.header.height, height);
+			assert_eq!(block.hash(), header.hash());
+		}
+		assert!(chain.get_block_by_height(5).is_err());
+	}
+	clean_output_dir(chain_dir);
+}
+
+/// Test the difficulty window is bounded and runs backward from the parent
+#[test]
+fn difficulty_window() {
+	let chain_dir = ".kepler.difficulty_window";
+	clean_output_dir(chain_dir);
+	{
+		let chain = mine_chain(chain_dir, 6);
+		let window = chain.difficulty_window(5, 3).unwrap();
+		assert_eq!(window.len(), 3);
+		for (i, info) in window.iter().enumerate() {
+			let header = chain.get_header_by_height(4 - i as u64).unwrap();
+			assert_eq!(info.block_hash, header.hash());
+		}
+
+		// Shorter than requested when running into genesis.
+		let window = chain.difficulty_window(2, 10).unwrap();
+		assert_eq!(window.len(), 2);
+		assert_eq!(
+			window[1].block_hash,
+			chain.get_header_by_height(0).unwrap().hash()
+		);
+
+		// Matches the full header based difficulty iterator from the head.
+		let head = chain.head().unwrap();
+		let window = chain.difficulty_window(head.height + 1, 4).unwrap();
+		let expected: Vec<_> = chain.difficulty_iter().unwrap().take(4).collect();
+		assert_eq!(window, expected);
+
+		assert!(chain.difficulty_window(0, 3).is_err());
+	}
+	clean_output_dir(chain_dir);
+}
+
+/// Test a block can be validated against the current head without accepting it
+#[test]
+fn validate_block_against_tip() {
+	let chain_dir = ".kepler.validate_block_against_tip";
+	clean_output_dir(chain_dir);
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	{
+		let chain = mine_chain(chain_dir, 3);
+		let keychain = ExtKeychain::from_random_seed(false).unwrap();
+		let prev = chain.head_header().unwrap();
+		let next_header_info = consensus::next_difficulty(1, chain.difficulty_iter().unwrap());
+		let pk = ExtKeychainPath::new(1, 3, 0, 0, 0).to_identifier();
+		let reward = libtx::reward::output(
+			&keychain,
+			&libtx::ProofBuilder::new(&keychain),
+			&pk,
+			0,
+			prev.height + 1,
+			false,
+		)
+		.unwrap();
+		let mut b =
+			core::core::Block::new(&prev, vec![], next_header_info.clone().difficulty, reward)
+				.unwrap();
+		b.header.timestamp = prev.timestamp + Duration::seconds(60);
+		b.header.pow.secondary_scaling = next_header_info.secondary_scaling;
+		chain.set_txhashset_roots(&mut b).unwrap();
+		let edge_bits = global::min_edge_bits();
+		b.header.pow.proof.edge_bits = edge_bits;
+		pow::pow_size(
+			&mut b.header,
+			next_header_info.difficulty,
+			global::proofsize(),
+			edge_bits,
+		)
+		.unwrap();
+		b.header.pow.proof.edge_bits = edge_bits;
+
+		let head = chain.head().unwrap();
+
+		// Valid block passes the dry-run without becoming the head.
+		chain.validate_block_against_tip(&b).unwrap();
+		assert_eq!(chain.head().unwrap(), head);
+		assert!(!chain.block_exists(b.hash()).unwrap());
+
+		// Dropping the coinbase output breaks the block (pow is unaffected).
+		let mut invalid = b.clone();
+		invalid.outputs_mut().clear();
+		assert!(chain.validate_block_against_tip(&invalid).is_err());
+		assert_eq!(chain.head().unwrap(), head);
+
+		// The dry-runs left no trace so the block is still accepted for real.
+		chain.process_block(b.clone(), Options::MINE).unwrap();
+		assert_eq!(chain.head().unwrap().last_block_h, b.hash());
+	}
+	clean_output_dir(chain_dir);
+}
+
+/// Test accept_block returns the accepted branch tip, even when not the head
+#[test]
+fn accept_block_returns_tip() {
+	let chain_dir = ".kepler.accept_block";
+	clean_output_dir(chain_dir);
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	{
+		let kc = ExtKeychain::from_random_seed(false).unwrap();
+		let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+
+		let block_a = prepare_block(&kc, &chain.head_header().unwrap(), &chain, 2);
+		let tip = chain
+			.accept_block(block_a.clone(), chain::Options::SKIP_POW)
+			.unwrap();
+		assert_eq!(tip.total_difficulty, block_a.header.total_difficulty());
+		assert_eq!(tip, chain.head().unwrap());
+
+		// A fork with less work is accepted without becoming the head.
+		let genesis = chain.get_header_by_height(0).unwrap();
+		let block_b = prepare_block(&kc, &genesis, &chain, 1);
+		let tip = chain
+			.accept_block(block_b.clone(), chain::Options::SKIP_POW)
+			.unwrap();
+		assert_eq!(tip.total_difficulty, block_b.header.total_difficulty());
+		assert_eq!(tip.last_block_h, block_b.hash());
+		assert_eq!(chain.head().unwrap().last_block_h, block_a.hash());
+	}
+	clean_output_dir(chain_dir);
+}
+
+/// Test a header whose output MMR size shrinks relative to its parent is rejected
+#[test]
+fn reject_mmr_size_regression() {
+	let chain_dir = ".kepler.mmr_size_regression";
+	clean_output_dir(chain_dir);
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	{
+		let kc = ExtKeychain::from_random_seed(false).unwrap();
+		let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+
+		let block_a = prepare_block(&kc, &chain.head_header().unwrap(), &chain, 1);
+		process_block(&chain, &block_a);
+
+		let mut block_b = prepare_block(&kc, &block_a.header, &chain, 2);
+		block_b.header.output_mmr_size = block_a.header.output_mmr_size - 1;
+
+		match chain
+			.process_block_header(&block_b.header, chain::Options::SKIP_POW)
+			.map_err(|e| e.kind())
+		{
+			Err(ErrorKind::InvalidMMRSize) => {}
+			res => panic!("expected InvalidMMRSize, got {:?}", res),
+		}
+	}
+	clean_output_dir(chain_dir);
+}
+
+/// Test full validation reports the height of a corrupted block in the db
+#[test]
+fn validate_reports_invalid_block_height() {
+	let chain_dir = ".kepler.validate_invalid_block";
+	clean_output_dir(chain_dir);
+	{
+		let chain = mine_chain(chain_dir, 6);
+		chain.validate(false).unwrap();
+
+		// Flip the coinbase flag on the reward output of the block at height 3
+		// and write it back to the db under the same hash.
+		let header = chain.get_header_by_height(3).unwrap();
+		let mut block = chain.get_block(&header.hash()).unwrap();
+		block.outputs_mut()[0].features = OutputFeatures::Plain;
+		{
+			let batch = chain.store().batch().unwrap();
+			batch.save_block(&block).unwrap();
+			batch.commit().unwrap();
+		}
+
+		// Fast validation does not look at individual blocks.
+		chain.validate(true).unwrap();
+
+		match chain.validate(false).map_err(|e| e.kind()) {
+			Err(ErrorKind::InvalidBlockAtHeight(height, _)) => assert_eq!(height, 3),
+			res => panic!("expected invalid block at height 3, got {:?}", res),
+		}
+	}
+	clean_output_dir(chain_dir);
+}
+
+/// Test batch tx validation reports per-tx results, including conflicts within the batch
+#[test]
+fn validate_tx_batch() {
+	let chain_dir = ".kepler.validate_tx_batch";
+	clean_output_dir(chain_dir);
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	{
+		let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+		let kc = ExtKeychain::from_random_seed(false).unwrap();
+		let pb = ProofBuilder::new(&kc);
+
+		let block_a = prepare_block_key_idx(&kc, &chain.head_header().unwrap(), &chain, 1, 1);
+		process_block(&chain, &block_a);
+		let block_b = prepare_block_key_idx(&kc, &block_a.header, &chain, 2, 2);
+		process_block(&chain, &block_b);
+
+		let reward = consensus::reward(block_b.header.height, 0);
+		let spend = |coinbase_idx: u32, out_idx: u32| {
+			build::transaction(
+				KernelFeatures::Plain { fee: 20000 },
+				vec![
+					build::coinbase_input(
+						reward,
+						ExtKeychainPath::new(1, coinbase_idx, 0, 0, 0).to_identifier(),
+					),
+					build::output(
+						reward - 20000,
+						ExtKeychainPath::new(1, out_idx, 0, 0, 0).to_identifier(),
+					),
+				],
+				&kc,
+				&pb,
+			)
+			.unwrap()
+		};
+
+		let tx_a = spend(1, 30);
+		let tx_conflict = spend(1, 31);
+		let tx_b = spend(2, 32);
+		let tx_unknown = spend(40, 33);
+
+		// Each of these is fine on its own against the current UTXO set.
+		chain.validate_tx(&tx_conflict).unwrap();
+
+		let results: Vec<_> = chain
+			.validate_tx_batch(&[tx_a, tx_conflict.clone(), tx_b, tx_unknown.clone()])
+			.into_iter()
+			.map(|res| res.map_err(|e| e.kind()))
+			.collect();
+		assert_eq!(
+			results,
+			vec![
+				Ok(()),
+				Err(ErrorKind::AlreadySpent(
+					tx_conflict.inputs()[0].commitment()
+				)),
+				Ok(()),
+				Err(ErrorKind::AlreadySpent(tx_unknown.inputs()[0].commitment())),
+			]
+		);
+	}
+	clean_output_dir(chain_dir);
+}
+
+/// Test header lookup by hash distinguishes main chain headers from fork headers
+#[test]
+fn header_at_hash() {
+	let chain_dir = ".kepler.header_at_hash";
+	clean_output_dir(chain_dir);
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	{
+		let kc = ExtKeychain::from_random_seed(false).unwrap();
+		let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+		let genesis = chain.head_header().unwrap();
+
+		let block_a = prepare_block(&kc, &genesis, &chain, 2);
+		process_block(&chain, &block_a);
+
+		// A competing block at the same height with less work.
+		let block_b = prepare_block(&kc, &genesis, &chain, 1);
+		process_block(&chain, &block_b);
+		assert_eq!(chain.head().unwrap().last_block_h, block_a.hash());
+
+		let (header, on_main_chain) = chain.header_at_hash(&block_a.hash()).unwrap();
+		assert_eq!(header, block_a.header);
+		assert!(on_main_chain);
+
+		let (header, on_main_chain) = chain.header_at_hash(&block_b.hash()).unwrap();
+		assert_eq!(header, block_b.header);
+		assert!(!on_main_chain);
+
+		// A fork header beyond the header head is not on the main chain either.
+		let block_c = prepare_block_key_idx(&kc, &block_b.header, &chain, 1, 3);
+		process_block(&chain, &block_c);
+		assert_eq!(chain.header_head().unwrap().last_block_h, block_a.hash());
+		let (header, on_main_chain) = chain.header_at_hash(&block_c.hash()).unwrap();
+		assert_eq!(header, block_c.header);
+		assert!(!on_main_chain);
+
+		assert!(chain.header_at_hash(&Hash::default()).is_err());
+	}
+	clean_output_dir(chain_dir);
+}
+
+/// Test iterating over the blocks on the current chain from a given height
+#[test]
+fn blocks_from() {
+	let chain_dir = ".kepler.blocks_from";
+	clean_output_dir(chain_dir);
+	{
+		let chain = mine_chain(chain_dir, 6);
+		assert_eq!(chain.head().unwrap().height, 5);
+
+		let blocks: Vec<_> = chain.blocks_from(2).map(|b| b.unwrap()).collect();
+		assert_eq!(
+			blocks.iter().map(|b| b.header.height).collect::<Vec<_>>(),
+			vec![2, 3, 4, 5]
+		);
+		for b in &blocks {
+			assert_eq!(
+				b.hash(),
+				chain.get_header_by_height(b.header.height).unwrap().hash()
+			);
+		}
+
+		assert_eq!(chain.blocks_from(0).count(), 6);
+		assert_eq!(chain.blocks_from(6).count(), 0);
+	}
+	clean_output_dir(chain_dir);
+}
+
 // Use diff as both diff *and* key_idx for convenience (deterministic private key for test blocks)
 fn prepare_block<K>(kc: &K, prev: &BlockHeader, chain: &Chain, diff: u64) -> Block
 where
@@ -15,12 +15,12 @@
 use self::chain::types::NoopAdapter;
 use self::chain::ErrorKind;
 use self::core::core::verifier_cache::LruVerifierCache;
-use self::core::core::KernelFeatures;
+use self::core::core::{KernelFeatures, OutputFeatures, OutputIdentifier};
 use self::core::global::{self, ChainTypes};
 use self::core::libtx::{self, build, ProofBuilder};
 use self::core::pow::Difficulty;
 use self::core::{consensus, pow};
-use self::keychain::{ExtKeychain, ExtKeychainPath, Keychain};
+use self::keychain::{ExtKeychain, ExtKeychainPath, Keychain, SwitchCommitmentType};
 use self::util::RwLock;
 use chrono::Duration;
 use env_logger;
@@ -298,3 +298,83 @@ fn test_coinbase_maturity() {
 	// Cleanup chain directory
 	clean_output_dir(chain_dir);
 }
+
+#[test]
+fn test_output_maturity_height() {
+	let _ = env_logger::init();
+	let chain_dir = ".kepler_output_maturity";
+	clean_output_dir(chain_dir);
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+
+	let genesis_block = pow::mine_genesis_block().unwrap();
+
+	let verifier_cache = Arc::new(RwLock::new(LruVerifierCache::new()));
+
+	{
+		let chain = chain::Chain::init(
+			chain_dir.to_string(),
+			Arc::new(NoopAdapter {}),
+			genesis_block,
+			pow::verify_size,
+			verifier_cache,
+			false,
+		)
+		.unwrap();
+
+		let prev = chain.head_header().unwrap();
+
+		let keychain = ExtKeychain::from_random_seed(false).unwrap();
+		let builder = ProofBuilder::new(&keychain);
+		let key_id1 = ExtKeychainPath::new(1, 1, 0, 0, 0).to_identifier();
+		let key_id2 = ExtKeychainPath::new(1, 2, 0, 0, 0).to_identifier();
+
+		let next_header_info = consensus::next_difficulty(1, chain.difficulty_iter().unwrap());
+		let height = prev.height + 1;
+		let reward =
+			libtx::reward::output(&keychain, &builder, &key_id1, 0, height, false).unwrap();
+		let mut block = core::core::Block::new(&prev, vec![], Difficulty::min(), reward).unwrap();
+		block.header.timestamp = prev.timestamp + Duration::seconds(60);
+		block.header.pow.secondary_scaling = next_header_info.secondary_scaling;
+
+		chain.set_txhashset_roots(&mut block).unwrap();
+
+		pow::pow_size(
+			&mut block.header,
+			next_header_info.difficulty,
+			global::proofsize(),
+			global::min_edge_bits(),
+		)
+		.unwrap();
+
+		let coinbase_output = block.outputs()[0];
+		assert!(coinbase_output.is_coinbase());
+
+		chain
+			.process_block(block.clone(), chain::Options::MINE)
+			.unwrap();
+
+		// The coinbase output matures coinbase_maturity blocks after the block it
+		// was mined in.
+		assert_eq!(
+			chain
+				.output_maturity_height(&OutputIdentifier::from_output(&coinbase_output))
+				.unwrap(),
+			Some(block.header.height + global::coinbase_maturity())
+		);
+
+		// An output the chain has never seen has no maturity height.
+		let commit = keychain
+			.commit(5, &key_id2, SwitchCommitmentType::Regular)
+			.unwrap();
+		let unknown = OutputIdentifier::new(OutputFeatures::Plain, &commit);
+		assert_eq!(chain.output_maturity_height(&unknown).unwrap(), None);
+
+		// A known commitment with the wrong features does not match the output
+		// in the output MMR, which is an error rather than an unknown output.
+		let mismatched =
+			OutputIdentifier::new(OutputFeatures::Plain, &coinbase_output.commitment());
+		assert!(chain.output_maturity_height(&mismatched).is_err());
+	}
+	// Cleanup chain directory
+	clean_output_dir(chain_dir);
+}
@@ -1,4 +1,4 @@
-use kepler_core::core::{Block, CompactBlock, Transaction};
+use kepler_core::core::{Block, BlockHeader, CompactBlock, Transaction};
 use kepler_core::ser;
 use std::fs::{self, File};
 use std::path::Path;
@@ -30,6 +30,18 @@ fn main() {
 		CompactBlock::from(Block::default()),
 	)
 	.unwrap();
+	generate(
+		"block_header_read_v1",
+		ser::ProtocolVersion(1),
+		BlockHeader::default(),
+	)
+	.unwrap();
+	generate(
+		"block_header_read_v2",
+		ser::ProtocolVersion(2),
+		BlockHeader::default(),
+	)
+	.unwrap();
 }
 
 fn generate<W: ser::Writeable>(
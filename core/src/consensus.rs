@@ -62,11 +62,11 @@ pub fn reward(height: u64, fee: u64) -> u64 {
 	if height == 0 {
 		return 42_000_000 * KEPLER_BASE;
 	}
-	let halvings = height / HALVING_INTERVAL;
+	let halvings = height / global::halving_interval();
 	if halvings >= 64 {
 		return NANO_KEPLER + fee;
 	}
-	(max(INITIAL_REWARD >> halvings, NANO_KEPLER)).saturating_add(fee)
+	(max(global::initial_reward() >> halvings, NANO_KEPLER)).saturating_add(fee)
 }
 
 /// Target ratio of secondary proof of work to primary proof of work,
@@ -32,15 +32,39 @@ use chrono::naive::{MAX_DATE, MIN_DATE};
 use chrono::prelude::{DateTime, NaiveDateTime, Utc};
 use chrono::Duration;
 use keychain::{self, BlindingFactor};
+use rand::{thread_rng, Rng};
+use std::cmp;
 use std::collections::HashSet;
 use std::convert::TryInto;
 use std::fmt;
 use std::iter::FromIterator;
 use std::sync::Arc;
+use std::time;
 use util::from_hex;
 use util::RwLock;
 use util::{secp, static_secp_instance};
 
+/// Max serialized size of an input: features and commitment.
+const MAX_INPUT_SIZE: usize = 1 + secp::constants::PEDERSEN_COMMITMENT_SIZE;
+
+/// Max serialized size of an output: features, commitment and a
+/// length-prefixed range proof.
+const MAX_OUTPUT_SIZE: usize =
+	1 + secp::constants::PEDERSEN_COMMITMENT_SIZE + 8 + secp::constants::MAX_PROOF_SIZE;
+
+/// Max serialized size of a kernel: features with fee and lock height (as in
+/// v1), excess and signature.
+const MAX_KERNEL_SIZE: usize =
+	1 + 8 + 8 + secp::constants::PEDERSEN_COMMITMENT_SIZE + secp::constants::AGG_SIGNATURE_SIZE;
+
+/// Size in bytes of the pre-pow fields preceding the nonce, leaving out the
+/// optional ones: version (2) + height (8) + timestamp (8) + prev_hash,
+/// prev_root, output_root, range_proof_root, kernel_root (5 * 32) +
+/// total_kernel_offset (32) + output_mmr_size, kernel_mmr_size (2 * 8) +
+/// total_difficulty (8) + secondary_scaling (4).
+const PRE_POW_FIXED_LEN: usize =
+	2 + 8 + 8 + 5 * Hash::LEN + secp::constants::SECRET_KEY_SIZE + 2 * 8 + Difficulty::LEN + 4;
+
 /// Errors thrown by Block validation
 #[derive(Debug, Clone, Eq, PartialEq, Fail)]
 pub enum Error {
@@ -81,6 +105,27 @@ pub enum Error {
 	Serialization(ser::Error),
 	/// Other unspecified error condition
 	Other(String),
+	/// Error annotated with the hash and height of the block it relates to
+	Context {
+		/// Hash of the offending block
+		block_hash: Hash,
+		/// Height of the offending block
+		height: u64,
+		/// The underlying error
+		error: Box<Error>,
+	},
+}
+
+impl Error {
+	/// Attach the hash and height of the offending block to this error so it
+	/// can be correlated in logs further up the stack.
+	pub fn with_context(self, block_hash: Hash, height: u64) -> Error {
+		Error::Context {
+			block_hash,
+			height,
+			error: Box::new(self),
+		}
+	}
 }
 
 impl From<committed::Error> for Error {
@@ -115,7 +160,14 @@ impl From<keychain::Error> for Error {
 
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		write!(f, "Block Error (display needs implementation")
+		match self {
+			Error::Context {
+				block_hash,
+				height,
+				error,
+			} => write!(f, "Block {} at height {}: {:?}", block_hash, height, error),
+			_ => write!(f, "Block Error (display needs implementation"),
+		}
 	}
 }
 
@@ -175,8 +227,27 @@ impl Hashed for HeaderEntry {
 	}
 }
 
+impl HeaderEntry {
+	/// Total difficulty accumulated on chain up to and including this header.
+	pub fn total_difficulty(&self) -> Difficulty {
+		self.total_difficulty
+	}
+
+	/// Difficulty information for this header, given the total difficulty of
+	/// its parent (zero for genesis).
+	pub fn header_info(&self, prev_total_difficulty: Difficulty) -> consensus::HeaderInfo {
+		consensus::HeaderInfo::new(
+			self.hash,
+			self.timestamp,
+			self.total_difficulty - prev_total_difficulty,
+			self.secondary_scaling,
+			self.is_secondary,
+		)
+	}
+}
+
 /// Some type safety around header versioning.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Serialize)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct HeaderVersion(pub u16);
 
 impl From<HeaderVersion> for u16 {
@@ -198,8 +269,40 @@ impl Readable for HeaderVersion {
 	}
 }
 
+/// First header version carrying the extra commitment.
+/// Pre-staged for a future hard fork: `consensus::valid_header_version`
+/// currently rejects every version past 3, so no valid header carries it yet.
+pub const EXTRA_COMMITMENT_HEADER_VERSION: HeaderVersion = HeaderVersion(4);
+
+/// Which optional, version gated, fields a header carries. All header
+/// (de)serialization goes through this so the version matrix lives in one
+/// place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeaderFieldset {
+	/// Extra commitment following the kernel MMR size
+	pub extra_commitment: bool,
+}
+
+impl HeaderFieldset {
+	/// The optional fields present at the provided header version.
+	pub fn for_version(version: HeaderVersion) -> HeaderFieldset {
+		HeaderFieldset {
+			extra_commitment: version >= EXTRA_COMMITMENT_HEADER_VERSION,
+		}
+	}
+
+	/// Size in bytes of the optional fields in the pre-pow.
+	pub fn pre_pow_len(&self) -> usize {
+		if self.extra_commitment {
+			Hash::LEN
+		} else {
+			0
+		}
+	}
+}
+
 /// Block header, fairly standard compared to other blockchains.
-#[derive(Clone, Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct BlockHeader {
 	/// Version of the block
 	pub version: HeaderVersion,
@@ -225,6 +328,11 @@ pub struct BlockHeader {
 	pub output_mmr_size: u64,
 	/// Total size of the kernel MMR after applying this block
 	pub kernel_mmr_size: u64,
+	/// 32 byte commitment anchored in the block (sidechains, rollups),
+	/// `ZERO_HASH` when unused. Only serialized (and covered by the PoW) from
+	/// `EXTRA_COMMITMENT_HEADER_VERSION` onwards, and always `ZERO_HASH` when
+	/// read at earlier versions. Does not take part in any sums.
+	pub extra_commitment: Hash,
 	/// Proof of work and related
 	pub pow: ProofOfWork,
 }
@@ -244,6 +352,7 @@ impl Default for BlockHeader {
 			total_kernel_offset: BlindingFactor::zero(),
 			output_mmr_size: 0,
 			kernel_mmr_size: 0,
+			extra_commitment: ZERO_HASH,
 			pow: ProofOfWork::default(),
 		}
 	}
@@ -290,6 +399,12 @@ fn read_block_header(reader: &mut dyn Reader) -> Result<BlockHeader, ser::Error>
 	let kernel_root = Hash::read(reader)?;
 	let total_kernel_offset = BlindingFactor::read(reader)?;
 	let (output_mmr_size, kernel_mmr_size) = ser_multiread!(reader, read_u64, read_u64);
+	let fields = HeaderFieldset::for_version(version);
+	let extra_commitment = if fields.extra_commitment {
+		Hash::read(reader)?
+	} else {
+		ZERO_HASH
+	};
 	let pow = ProofOfWork::read(reader)?;
 
 	if timestamp > MAX_DATE.and_hms(0, 0, 0).timestamp()
@@ -310,6 +425,7 @@ fn read_block_header(reader: &mut dyn Reader) -> Result<BlockHeader, ser::Error>
 		total_kernel_offset,
 		output_mmr_size,
 		kernel_mmr_size,
+		extra_commitment,
 		pow,
 	})
 }
@@ -338,6 +454,9 @@ impl BlockHeader {
 			[write_u64, self.output_mmr_size],
 			[write_u64, self.kernel_mmr_size]
 		);
+		if self.fieldset().extra_commitment {
+			writer.write_fixed_bytes(&self.extra_commitment)?;
+		}
 		Ok(())
 	}
 
@@ -346,6 +465,13 @@ impl BlockHeader {
 	/// for consistency with how this call is performed everywhere
 	/// else
 	pub fn pre_pow(&self) -> Vec<u8> {
+		self.pre_pow_template()
+	}
+
+	/// Return the pre-pow, with the nonce as its final 8 bytes.
+	/// Only the nonce changes between mining attempts on the same header so
+	/// miners can compute this once and use `splice_nonce` per attempt.
+	pub fn pre_pow_template(&self) -> Vec<u8> {
 		let mut header_buf = vec![];
 		{
 			let mut writer = ser::BinWriter::default(&mut header_buf);
@@ -356,12 +482,52 @@ impl BlockHeader {
 		header_buf
 	}
 
+	/// Byte offset of the nonce in the pre-pow buffer, the fixed pre-pow
+	/// fields followed by the optional fields (see `HeaderFieldset`).
+	pub fn pre_pow_nonce_offset(&self) -> usize {
+		PRE_POW_FIXED_LEN + self.fieldset().pre_pow_len()
+	}
+
+	/// Verify the header timestamp is strictly later than the previous header
+	/// and no more than 12 block intervals in the future.
+	pub fn verify_timestamp(&self, prev: &BlockHeader) -> Result<(), Error> {
+		if self.timestamp <= prev.timestamp {
+			return Err(Error::InvalidBlockTime);
+		}
+		if self.timestamp > Utc::now() + Duration::seconds(12 * (consensus::BLOCK_TIME_SEC as i64))
+		{
+			return Err(Error::InvalidBlockTime);
+		}
+		Ok(())
+	}
+
+	/// The optional fields carried by this header given its version.
+	pub fn fieldset(&self) -> HeaderFieldset {
+		HeaderFieldset::for_version(self.version)
+	}
+
+	/// Overwrite the nonce (big-endian, the final 8 bytes) of a pre-pow buffer
+	/// produced by `pre_pow_template` or `pre_pow`.
+	///
+	/// Panics if the buffer is too short to hold a nonce.
+	pub fn splice_nonce(buf: &mut [u8], nonce: u64) {
+		assert!(buf.len() >= 8, "pre-pow buffer too short for a nonce");
+		let offset = buf.len() - 8;
+		buf[offset..].copy_from_slice(&nonce.to_be_bytes());
+	}
+
 	/// Constructs a header given pre_pow string, nonce, and proof
 	pub fn from_pre_pow_and_proof(
 		pre_pow: String,
 		nonce: u64,
 		proof: Proof,
 	) -> Result<Self, Error> {
+		// Proofs are serialized as exactly proofsize() nonces, reject any
+		// other length up front rather than failing (or panicking) below.
+		if proof.nonces.len() != global::proofsize() {
+			return Err(Error::InvalidPow);
+		}
+
 		// Convert hex pre pow string
 		let mut header_bytes = from_hex(pre_pow)
 			.map_err(|e| Error::Serialization(ser::Error::HexError(e.to_string())))?;
@@ -401,6 +567,141 @@ impl BlockHeader {
 	pub fn total_kernel_offset(&self) -> BlindingFactor {
 		self.total_kernel_offset.clone()
 	}
+
+	/// Skeleton of a child header building on this one, with the version,
+	/// height, prev_hash, total_kernel_offset (the caller adds the child's
+	/// own offset) and total_difficulty derived from this header.
+	/// Roots, sizes and timestamp are left for the caller to fill in.
+	pub fn next_skeleton(&self, difficulty: Difficulty) -> BlockHeader {
+		let height = self.height + 1;
+		BlockHeader {
+			version: consensus::header_version(height),
+			height,
+			prev_hash: self.hash(),
+			total_kernel_offset: self.total_kernel_offset(),
+			pow: ProofOfWork {
+				total_difficulty: difficulty + self.total_difficulty(),
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+
+	/// Field-level differences between this header and another.
+	/// Fields are reported in serialization order.
+	pub fn diff(&self, other: &BlockHeader) -> Vec<HeaderFieldDiff> {
+		let fields = vec![
+			(
+				"version",
+				self.version.0.to_string(),
+				other.version.0.to_string(),
+			),
+			("height", self.height.to_string(), other.height.to_string()),
+			(
+				"timestamp",
+				self.timestamp.timestamp().to_string(),
+				other.timestamp.timestamp().to_string(),
+			),
+			(
+				"prev_hash",
+				self.prev_hash.to_hex(),
+				other.prev_hash.to_hex(),
+			),
+			(
+				"prev_root",
+				self.prev_root.to_hex(),
+				other.prev_root.to_hex(),
+			),
+			(
+				"output_root",
+				self.output_root.to_hex(),
+				other.output_root.to_hex(),
+			),
+			(
+				"range_proof_root",
+				self.range_proof_root.to_hex(),
+				other.range_proof_root.to_hex(),
+			),
+			(
+				"kernel_root",
+				self.kernel_root.to_hex(),
+				other.kernel_root.to_hex(),
+			),
+			(
+				"total_kernel_offset",
+				self.total_kernel_offset.to_hex(),
+				other.total_kernel_offset.to_hex(),
+			),
+			(
+				"output_mmr_size",
+				self.output_mmr_size.to_string(),
+				other.output_mmr_size.to_string(),
+			),
+			(
+				"kernel_mmr_size",
+				self.kernel_mmr_size.to_string(),
+				other.kernel_mmr_size.to_string(),
+			),
+			(
+				"extra_commitment",
+				self.extra_commitment.to_hex(),
+				other.extra_commitment.to_hex(),
+			),
+			(
+				"total_difficulty",
+				self.pow.total_difficulty.to_num().to_string(),
+				other.pow.total_difficulty.to_num().to_string(),
+			),
+			(
+				"secondary_scaling",
+				self.pow.secondary_scaling.to_string(),
+				other.pow.secondary_scaling.to_string(),
+			),
+			(
+				"nonce",
+				self.pow.nonce.to_string(),
+				other.pow.nonce.to_string(),
+			),
+			(
+				"proof",
+				format!("{:?}", self.pow.proof),
+				format!("{:?}", other.pow.proof),
+			),
+		];
+		fields
+			.into_iter()
+			.filter(|(_, old, new)| old != new)
+			.map(|(field, old, new)| HeaderFieldDiff { field, old, new })
+			.collect()
+	}
+}
+
+/// A single field that differs between two block headers, with both values
+/// rendered for display (hashes as hex, numeric fields as decimal).
+/// Intended for debugging header disagreements between nodes, not consensus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderFieldDiff {
+	/// Name of the header field
+	pub field: &'static str,
+	/// Value on the header `diff` was called on
+	pub old: String,
+	/// Value on the header passed to `diff`
+	pub new: String,
+}
+
+/// Time spent in each phase of full block validation, see
+/// `Block::validate_with_metrics`. Phases not reached (validation failed
+/// earlier) are left untouched.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationMetrics {
+	/// Body validation (weight, sorting, rangeproofs and kernel signatures)
+	pub body: time::Duration,
+	/// Kernel lock height checks
+	pub lock_heights: time::Duration,
+	/// Coinbase sum verification
+	pub coinbase: time::Duration,
+	/// Kernel sum verification
+	pub kernel_sums: time::Duration,
 }
 
 impl From<UntrustedBlockHeader> for BlockHeader {
@@ -423,8 +724,9 @@ impl Readable for UntrustedBlockHeader {
 			// refuse blocks more than 12 blocks intervals in future (as in bitcoin)
 			// TODO add warning in p2p code if local time is too different from peers
 			error!(
-				"block header {} validation error: block time is more than 12 blocks in future",
-				header.hash()
+				"block header {} at {} validation error: block time is more than 12 blocks in future",
+				header.hash(),
+				header.height
 			);
 			return Err(ser::Error::CorruptedData);
 		}
@@ -439,15 +741,17 @@ impl Readable for UntrustedBlockHeader {
 
 		if !header.pow.is_primary() && !header.pow.is_secondary() {
 			error!(
-				"block header {} validation error: invalid edge bits",
-				header.hash()
+				"block header {} at {} validation error: invalid edge bits",
+				header.hash(),
+				header.height
 			);
 			return Err(ser::Error::CorruptedData);
 		}
 		if let Err(e) = verify_size(&header) {
 			error!(
-				"block header {} validation error: invalid POW: {}",
+				"block header {} at {} validation error: invalid POW: {}",
 				header.hash(),
+				header.height,
 				e
 			);
 			return Err(ser::Error::CorruptedData);
@@ -593,6 +897,12 @@ impl Block {
 		Block { header, body }.cut_through()
 	}
 
+	/// Build a compact block (with a random nonce) from this block, also
+	/// sending the outputs listed in keep_full in full.
+	pub fn to_compact_block(&self, keep_full: &HashSet<Commitment>) -> CompactBlock {
+		CompactBlock::from_block_keep_full(self, thread_rng().gen(), keep_full)
+	}
+
 	/// Build a new empty block from a specified header
 	pub fn with_header(header: BlockHeader) -> Block {
 		Block {
@@ -618,35 +928,23 @@ impl Block {
 			.with_output(reward_out)
 			.with_kernel(reward_kern);
 
+		// Derive the new header from the previous one (height, version etc.)
+		let mut header = prev.next_skeleton(difficulty);
+
 		// Now add the kernel offset of the previous block for a total
-		let total_kernel_offset = committed::sum_kernel_offsets(
+		header.total_kernel_offset = committed::sum_kernel_offsets(
 			vec![agg_tx.offset.clone(), prev.total_kernel_offset.clone()],
 			vec![],
 		)?;
 
-		// Determine the height and associated version for the new header.
-		let height = prev.height + 1;
-		let version = consensus::header_version(height);
-
 		let now = Utc::now().timestamp();
-		let timestamp = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(now, 0), Utc);
+		header.timestamp = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(now, 0), Utc);
 
 		// Now build the block with all the above information.
 		// Note: We have not validated the block here.
 		// Caller must validate the block as necessary.
 		Block {
-			header: BlockHeader {
-				version,
-				height,
-				timestamp,
-				prev_hash: prev.hash(),
-				total_kernel_offset,
-				pow: ProofOfWork {
-					total_difficulty: difficulty + prev.pow.total_difficulty,
-					..Default::default()
-				},
-				..Default::default()
-			},
+			header,
 			body: agg_tx.into(),
 		}
 		.cut_through()
@@ -690,9 +988,43 @@ impl Block {
 		&mut self.body.kernels
 	}
 
+	/// Iterate over inputs, preferred over `inputs()` when only reading
+	pub fn inputs_iter(&self) -> impl Iterator<Item = &Input> {
+		self.body.inputs.iter()
+	}
+
+	/// Iterate over outputs, preferred over `outputs()` when only reading
+	pub fn outputs_iter(&self) -> impl Iterator<Item = &Output> {
+		self.body.outputs.iter()
+	}
+
+	/// Iterate over kernels, preferred over `kernels()` when only reading
+	pub fn kernels_iter(&self) -> impl Iterator<Item = &TxKernel> {
+		self.body.kernels.iter()
+	}
+
 	/// Sum of all fees (inputs less outputs) in the block
-	pub fn total_fees(&self) -> u64 {
-		self.body.fee()
+	pub fn total_fees(&self) -> Result<u64, Error> {
+		Ok(self.body.checked_fee()?)
+	}
+
+	/// Total value the miner is entitled to claim via the coinbase output(s),
+	/// i.e. the block reward at this height plus all fees in the block.
+	pub fn miner_subsidy(&self) -> Result<u64, Error> {
+		Ok(reward(self.header.height, self.total_fees()?))
+	}
+
+	/// Same as `cut_through` but also returns the commitments of the outputs
+	/// (and their spending inputs) that were eliminated, for auditing.
+	pub fn cut_through_report(self) -> Result<(Block, Vec<Commitment>), Error> {
+		let before = self.outputs_committed();
+		let block = self.cut_through()?;
+		let after: HashSet<Commitment> = HashSet::from_iter(block.outputs_committed());
+		let eliminated = before
+			.into_iter()
+			.filter(|commit| !after.contains(commit))
+			.collect();
+		Ok((block, eliminated))
 	}
 
 	/// Matches any output with a potential spending input, eliminating them
@@ -700,19 +1032,75 @@ impl Block {
 	/// elimination is stable with respect to the order of inputs and outputs.
 	/// Method consumes the block.
 	pub fn cut_through(self) -> Result<Block, Error> {
-		let mut inputs = self.inputs().clone();
-		let mut outputs = self.outputs().clone();
+		// We consume the block so take ownership of the body rather than
+		// cloning it.
+		let Block { header, body } = self;
+		let TransactionBody {
+			mut inputs,
+			mut outputs,
+			kernels,
+		} = body;
 		transaction::cut_through(&mut inputs, &mut outputs)?;
 
-		let kernels = self.kernels().clone();
-
 		// Initialize tx body and sort everything.
 		let body = TransactionBody::init(inputs, outputs, kernels, false)?;
 
-		Ok(Block {
-			header: self.header,
-			body,
+		Ok(Block { header, body })
+	}
+
+	/// Max theoretical size in bytes of a serialized block.
+	/// The body is bounded by whichever of inputs, outputs or kernels packs
+	/// the most bytes per unit of block weight (rounded up, so any mix of
+	/// them within the weight limit also fits), plus the largest header and
+	/// the three u64 body length prefixes.
+	pub fn max_serialized_size() -> u64 {
+		let max_weight = global::max_block_weight() as u64;
+		let max_body = [
+			(MAX_INPUT_SIZE, consensus::BLOCK_INPUT_WEIGHT),
+			(MAX_OUTPUT_SIZE, consensus::BLOCK_OUTPUT_WEIGHT),
+			(MAX_KERNEL_SIZE, consensus::BLOCK_KERNEL_WEIGHT),
+		]
+		.iter()
+		.map(|&(size, weight)| {
+			let (size, weight) = (size as u64, weight as u64);
+			(max_weight * size + weight - 1) / weight
 		})
+		.max()
+		.unwrap_or(0);
+
+		// fixed pre-pow fields, version gated fields (all of them, as carried by
+		// the highest version), nonce, edge_bits and the widest packed proof
+		let gated_len = HeaderFieldset::for_version(HeaderVersion(u16::max_value())).pre_pow_len();
+		let max_header = (PRE_POW_FIXED_LEN + gated_len) as u64
+			+ 8 + 1 + (63 * global::proofsize() as u64 + 7) / 8;
+
+		max_header + 3 * 8 + max_body
+	}
+
+	/// Read a block preceded by its u64 length in bytes. Exactly that many bytes
+	/// are consumed from the reader, so a malformed block can't over-read into
+	/// whatever follows it on the stream.
+	pub fn read_framed(reader: &mut dyn Reader) -> Result<Block, ser::Error> {
+		let len = reader.read_u64()?;
+		if len > Block::max_serialized_size() {
+			return Err(ser::Error::TooLargeReadErr);
+		}
+
+		// read_fixed_bytes caps individual reads, so read the frame in chunks
+		let mut buf = Vec::with_capacity(len as usize);
+		let mut remaining = len as usize;
+		while remaining > 0 {
+			let chunk = cmp::min(remaining, ser::MAX_FIXED_BYTES_READ);
+			buf.extend(reader.read_fixed_bytes(chunk)?);
+			remaining -= chunk;
+		}
+
+		let mut frame = &buf[..];
+		let block: UntrustedBlock = ser::deserialize(&mut frame, reader.protocol_version())?;
+		if !frame.is_empty() {
+			return Err(ser::Error::CorruptedData);
+		}
+		Ok(block.into())
 	}
 
 	/// "Lightweight" validation that we can perform quickly during read/deserialization.
@@ -752,19 +1140,82 @@ impl Block {
 		prev_kernel_offset: &BlindingFactor,
 		verifier: Arc<RwLock<dyn VerifierCache>>,
 	) -> Result<Commitment, Error> {
+		let (_utxo_sum, kernel_sum) = self.validate_with_sums(prev_kernel_offset, verifier)?;
+		Ok(kernel_sum)
+	}
+
+	/// Same as `validate` but returns both the utxo sum (outputs less inputs,
+	/// accounting for the block overage) and the kernel sum, so callers can
+	/// maintain running totals without recomputing them.
+	pub fn validate_with_sums(
+		&self,
+		prev_kernel_offset: &BlindingFactor,
+		verifier: Arc<RwLock<dyn VerifierCache>>,
+	) -> Result<(Commitment, Commitment), Error> {
+		self.validate_with_metrics(
+			prev_kernel_offset,
+			verifier,
+			&mut ValidationMetrics::default(),
+		)
+	}
+
+	/// Same as `validate_with_sums`, additionally recording how long each
+	/// validation phase took in the provided metrics.
+	pub fn validate_with_metrics(
+		&self,
+		prev_kernel_offset: &BlindingFactor,
+		verifier: Arc<RwLock<dyn VerifierCache>>,
+		metrics: &mut ValidationMetrics,
+	) -> Result<(Commitment, Commitment), Error> {
+		let start = time::Instant::now();
 		self.body.validate(Weighting::AsBlock, verifier)?;
+		metrics.body = start.elapsed();
 
+		let start = time::Instant::now();
 		self.verify_kernel_lock_heights()?;
+		metrics.lock_heights = start.elapsed();
+
+		let start = time::Instant::now();
 		self.verify_coinbase()?;
+		metrics.coinbase = start.elapsed();
 
 		// take the kernel offset for this block (block offset minus previous) and
 		// verify.body.outputs and kernel sums
-		let (_utxo_sum, kernel_sum) = self.verify_kernel_sums(
+		let start = time::Instant::now();
+		let sums = self.verify_kernel_sums(
 			self.header.overage(),
 			self.block_kernel_offset(prev_kernel_offset.clone())?,
 		)?;
+		metrics.kernel_sums = start.elapsed();
 
-		Ok(kernel_sum)
+		Ok(sums)
+	}
+
+	/// Validates a genesis block. Same as `validate` but the reward is optional
+	/// (a genesis block without coinbase output and kernel has no overage) and
+	/// the kernel offset is relative to a zero previous offset.
+	pub fn validate_genesis(&self, verifier: Arc<RwLock<dyn VerifierCache>>) -> Result<(), Error> {
+		if self.header.height != 0 {
+			return Err(Error::Other(format!(
+				"genesis block at height {}",
+				self.header.height
+			)));
+		}
+
+		self.body.validate(Weighting::AsBlock, verifier)?;
+		self.verify_kernel_lock_heights()?;
+
+		let had_reward = self.outputs_iter().any(|x| x.is_coinbase())
+			|| self.kernels_iter().any(|x| x.is_coinbase());
+		let overage = if had_reward {
+			self.verify_coinbase()?;
+			self.header.overage()
+		} else {
+			0
+		};
+
+		self.verify_kernel_sums(overage, self.block_kernel_offset(BlindingFactor::zero())?)?;
+		Ok(())
 	}
 
 	/// Validate the coinbase.body.outputs generated by miners.
@@ -776,34 +1227,64 @@ impl Block {
 			.outputs
 			.iter()
 			.filter(|out| out.is_coinbase())
-			.collect::<Vec<&Output>>();
+			.map(|out| out.commitment())
+			.collect::<Vec<Commitment>>();
 
 		let cb_kerns = self
 			.body
 			.kernels
 			.iter()
 			.filter(|kernel| kernel.is_coinbase())
-			.collect::<Vec<&TxKernel>>();
+			.map(|kernel| kernel.excess)
+			.collect::<Vec<Commitment>>();
 
-		{
+		let over_commit = {
 			let secp = static_secp_instance();
 			let secp = secp.lock();
-			let over_commit = secp.commit_value(reward(self.header.height, self.total_fees()))?;
+			secp.commit_value(self.miner_subsidy()?)?
+		};
 
-			let out_adjust_sum =
-				secp.commit_sum(map_vec!(cb_outs, |x| x.commitment()), vec![over_commit])?;
+		// Keep surfacing secp failures (e.g. summing no kernels) as such.
+		let sum = |positive: &[Commitment], negative: &[Commitment]| {
+			committed::sum_commits_slices(positive, negative).map_err(|e| match e {
+				committed::Error::Secp(e) => Error::Secp(e),
+				e => Error::Committed(e),
+			})
+		};
 
-			let kerns_sum = secp.commit_sum(cb_kerns.iter().map(|x| x.excess).collect(), vec![])?;
+		let out_adjust_sum = sum(&cb_outs, &[over_commit])?;
+		let kerns_sum = sum(&cb_kerns, &[])?;
 
-			// Verify the kernel sum equals the output sum accounting for block fees.
-			if kerns_sum != out_adjust_sum {
-				return Err(Error::CoinbaseSumMismatch);
-			}
+		// Verify the kernel sum equals the output sum accounting for block fees.
+		if kerns_sum != out_adjust_sum {
+			return Err(Error::CoinbaseSumMismatch);
 		}
 
 		Ok(())
 	}
 
+	/// Verify the proof of work on this block header, and that the difficulty
+	/// achieved by the proof meets the target difficulty claimed relative to
+	/// the previous header.
+	pub fn verify_pow(&self, prev: &BlockHeader) -> Result<(), Error> {
+		verify_size(&self.header).map_err(|_| Error::InvalidPow)?;
+
+		if self.header.total_difficulty() <= prev.total_difficulty() {
+			return Err(Error::InvalidPow);
+		}
+		let target_difficulty = self.header.total_difficulty() - prev.total_difficulty();
+		if self.header.pow.to_difficulty(self.header.height) < target_difficulty {
+			return Err(Error::InvalidPow);
+		}
+		Ok(())
+	}
+
+	/// Verify the block timestamp is strictly later than the previous header
+	/// and no more than 12 block intervals in the future.
+	pub fn verify_timestamp(&self, prev: &BlockHeader) -> Result<(), Error> {
+		self.header.verify_timestamp(prev)
+	}
+
 	fn verify_kernel_lock_heights(&self) -> Result<(), Error> {
 		for k in &self.body.kernels {
 			// check we have no kernels with lock_heights greater than current height
@@ -832,7 +1313,7 @@ pub struct UntrustedBlock(Block);
 impl Readable for UntrustedBlock {
 	fn read(reader: &mut dyn Reader) -> Result<UntrustedBlock, ser::Error> {
 		// we validate header here before parsing the body
-		let header = UntrustedBlockHeader::read(reader)?;
+		let header: BlockHeader = UntrustedBlockHeader::read(reader)?.into();
 		let body = TransactionBody::read(reader)?;
 
 		// Now "lightweight" validation of the block.
@@ -840,13 +1321,11 @@ impl Readable for UntrustedBlock {
 		// An example of this would be reading a block
 		// that exceeded the allowed number of inputs.
 		body.validate_read(Weighting::AsBlock).map_err(|e| {
+			let e = Error::from(e).with_context(header.hash(), header.height);
 			error!("read validation error: {}", e);
 			ser::Error::CorruptedData
 		})?;
-		let block = Block {
-			header: header.into(),
-			body,
-		};
+		let block = Block { header, body };
 		Ok(UntrustedBlock(block))
 	}
 }
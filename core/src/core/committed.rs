@@ -33,6 +33,13 @@ pub enum Error {
 	/// Kernel sums do not equal output sums.
 	#[fail(display = "Kernel sum mismatch")]
 	KernelSumMismatch,
+	/// Kernel sums do not equal output sums, carrying the utxo sum (outputs
+	/// less inputs, accounting for overage) and the kernel sum plus offset.
+	#[fail(
+		display = "Kernel sum mismatch: utxo sum {:?}, kernel sum {:?}",
+		_0, _1
+	)]
+	KernelSumMismatchWithSums(Commitment, Commitment),
 	/// Committed overage (fee or reward) is invalid
 	#[fail(display = "Invalid value")]
 	InvalidValue,
@@ -132,7 +139,10 @@ pub trait Committed {
 		let (kernel_sum, kernel_sum_plus_offset) = self.sum_kernel_excesses(&kernel_offset)?;
 
 		if utxo_sum != kernel_sum_plus_offset {
-			return Err(Error::KernelSumMismatch);
+			return Err(Error::KernelSumMismatchWithSums(
+				utxo_sum,
+				kernel_sum_plus_offset,
+			));
 		}
 
 		Ok((utxo_sum, kernel_sum))
@@ -152,6 +162,26 @@ pub fn sum_commits(
 	Ok(secp.commit_sum(positive, negative)?)
 }
 
+/// Slice based variant of `sum_commits`, summing positive and negative
+/// commitments (eliminating zero values) under a single lock of the secp
+/// instance.
+pub fn sum_commits_slices(
+	positive: &[Commitment],
+	negative: &[Commitment],
+) -> Result<Commitment, Error> {
+	let zero_commit = secp_static::commit_to_zero_value();
+	let non_zero = |commits: &[Commitment]| {
+		commits
+			.iter()
+			.filter(|x| **x != zero_commit)
+			.cloned()
+			.collect::<Vec<_>>()
+	};
+	let secp = static_secp_instance();
+	let secp = secp.lock();
+	Ok(secp.commit_sum(non_zero(positive), non_zero(negative))?)
+}
+
 /// Utility function to take sets of positive and negative kernel offsets as
 /// blinding factors, convert them to private key filtering zero values and
 /// summing all of them. Useful to build blocks.
@@ -17,14 +17,16 @@
 use crate::core::block::{Block, BlockHeader, Error, UntrustedBlockHeader};
 use crate::core::hash::{DefaultHashable, Hashed};
 use crate::core::id::ShortIdentifiable;
-use crate::core::{Output, ShortId, TxKernel};
+use crate::core::{Commitment, Output, ShortId, TxKernel};
 use crate::ser::{self, read_multi, Readable, Reader, VerifySortedAndUnique, Writeable, Writer};
 use rand::{thread_rng, Rng};
+use std::collections::HashSet;
 
 /// Container for full (full) outputs and kernels and kern_ids for a compact block.
 #[derive(Debug, Clone)]
 pub struct CompactBlockBody {
-	/// List of full outputs - specifically the coinbase output(s)
+	/// List of full outputs - the coinbase output(s) and any outputs
+	/// explicitly kept full
 	pub out_full: Vec<Output>,
 	/// List of full kernels - specifically the coinbase kernel(s)
 	pub kern_full: Vec<TxKernel>,
@@ -155,21 +157,34 @@ impl CompactBlock {
 		&self.body.kern_full
 	}
 
-	/// Get full (coinbase) outputs
+	/// Get full (coinbase and any kept full) outputs
 	pub fn out_full(&self) -> &Vec<Output> {
 		&self.body.out_full
 	}
-}
 
-impl From<Block> for CompactBlock {
-	fn from(block: Block) -> Self {
+	/// Build a compact block using the provided nonce for the kernel short_ids.
+	/// Blocks sent to peers should use a random nonce (see `From<Block>`),
+	/// a fixed nonce makes the short_ids reproducible for tests and tooling.
+	pub fn from_block_with_nonce(block: Block, nonce: u64) -> CompactBlock {
+		CompactBlock::from_block_keep_full(&block, nonce, &HashSet::new())
+	}
+
+	/// Build a compact block using the provided nonce, keeping the outputs
+	/// listed in keep_full in full alongside the coinbase.
+	/// Useful when we know the peer has not seen the txs they belong to.
+	/// Non-coinbase kernels are always sent as short_ids so the peer can still
+	/// look up (or request) the txs, and with them their inputs.
+	pub fn from_block_keep_full(
+		block: &Block,
+		nonce: u64,
+		keep_full: &HashSet<Commitment>,
+	) -> CompactBlock {
 		let header = block.header.clone();
-		let nonce = thread_rng().gen();
 
 		let out_full = block
 			.outputs()
 			.iter()
-			.filter(|x| x.is_coinbase())
+			.filter(|x| x.is_coinbase() || keep_full.contains(&x.commitment()))
 			.cloned()
 			.collect::<Vec<_>>();
 
@@ -196,6 +211,12 @@ impl From<Block> for CompactBlock {
 	}
 }
 
+impl From<Block> for CompactBlock {
+	fn from(block: Block) -> Self {
+		CompactBlock::from_block_with_nonce(block, thread_rng().gen())
+	}
+}
+
 /// Implementation of Writeable for a compact block, defines how to write the
 /// block to a binary writer. Differentiates between writing the block for the
 /// purpose of full serialization and the one of just extracting a hash.
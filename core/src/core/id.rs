@@ -40,22 +40,43 @@ impl<H: Hashed> ShortIdentifiable for H {
 	///   * drop the 2 most significant bytes (to return a 6 byte short_id)
 	///
 	fn short_id(&self, hash: &Hash, nonce: u64) -> ShortId {
+		ShortIdHasher::new(hash, nonce).short_id(&self.hash())
+	}
+}
+
+/// The siphasher24 keys for short_ids specific to a block hash and nonce.
+/// Derive these once and reuse them when generating short_ids for many
+/// items (i.e. all the kernels in the txpool) against the same compact block.
+#[derive(Clone, Copy, Debug)]
+pub struct ShortIdHasher {
+	k0: u64,
+	k1: u64,
+}
+
+impl ShortIdHasher {
+	/// Extract k0/k1 from the block hash hashed together with the nonce.
+	pub fn new(hash: &Hash, nonce: u64) -> ShortIdHasher {
 		// take the block hash and the nonce and hash them together
 		let hash_with_nonce = (hash, nonce).hash();
 
-		// we "use" core::hash::Hash in the outer namespace
-		// so doing this here in the fn to minimize collateral damage/confusion
-		use std::hash::Hasher;
-
 		// extract k0/k1 from the block_hash
 		let k0 = LittleEndian::read_u64(&hash_with_nonce.as_bytes()[0..8]);
 		let k1 = LittleEndian::read_u64(&hash_with_nonce.as_bytes()[8..16]);
 
+		ShortIdHasher { k0, k1 }
+	}
+
+	/// The short_id of an item given its (already computed) hash.
+	pub fn short_id(&self, item_hash: &Hash) -> ShortId {
+		// we "use" core::hash::Hash in the outer namespace
+		// so doing this here in the fn to minimize collateral damage/confusion
+		use std::hash::Hasher;
+
 		// initialize a siphasher24 with k0/k1
-		let mut sip_hasher = SipHasher24::new_with_keys(k0, k1);
+		let mut sip_hasher = SipHasher24::new_with_keys(self.k0, self.k1);
 
-		// hash our id (self.hash()) using the siphasher24 instance
-		sip_hasher.write(&self.hash().to_vec()[..]);
+		// hash our id (item hash) using the siphasher24 instance
+		sip_hasher.write(&item_hash.to_vec()[..]);
 		let res = sip_hasher.finish();
 
 		// construct a short_id from the resulting bytes (dropping the 2 most
@@ -154,6 +175,21 @@ mod test {
 		assert_eq!(ids, [id_1, id_3, id_2]);
 	}
 
+	#[test]
+	fn short_id_hasher_reuse() {
+		let block_hash = Hash::default();
+		let ids = [ShortId::from_bytes(&[1]), ShortId::from_bytes(&[2])];
+
+		// One hasher per (block hash, nonce) gives the same short_ids as
+		// deriving the keys again for every item.
+		for nonce in 0..3 {
+			let hasher = ShortIdHasher::new(&block_hash, nonce);
+			for id in ids.iter() {
+				assert_eq!(hasher.short_id(&id.hash()), id.short_id(&block_hash, nonce));
+			}
+		}
+	}
+
 	#[test]
 	fn test_short_id() {
 		// minimal struct for testing
@@ -248,6 +248,8 @@ pub enum Error {
 	InvalidKernelFeatures,
 	/// Signature verification error.
 	IncorrectSignature,
+	/// Sum of kernel fees overflows a u64.
+	FeeOverflow,
 	/// Underlying serialization error.
 	Serialization(ser::Error),
 }
@@ -538,6 +540,16 @@ impl Readable for TransactionBody {
 		let (input_len, output_len, kernel_len) =
 			ser_multiread!(reader, read_u64, read_u64, read_u64);
 
+		// Bound each count on its own (before any conversion to usize) so a
+		// crafted length prefix is rejected before we allocate anything for it.
+		let max_weight = global::max_block_weight() as u64;
+		if input_len > max_weight / consensus::BLOCK_INPUT_WEIGHT as u64
+			|| output_len > max_weight / consensus::BLOCK_OUTPUT_WEIGHT as u64
+			|| kernel_len > max_weight / consensus::BLOCK_KERNEL_WEIGHT as u64
+		{
+			return Err(ser::Error::TooLargeReadErr);
+		}
+
 		// Quick block weight check before proceeding.
 		// Note: We use weight_as_block here (inputs have weight).
 		let tx_block_weight = TransactionBody::weight_as_block(
@@ -663,6 +675,8 @@ impl TransactionBody {
 	}
 
 	/// Total fee for a TransactionBody is the sum of fees of all fee carrying kernels.
+	/// Saturates on overflow, so only suitable for non-consensus uses such as
+	/// fee based ordering; see `checked_fee` otherwise.
 	pub fn fee(&self) -> u64 {
 		self.kernels
 			.iter()
@@ -675,8 +689,27 @@ impl TransactionBody {
 			.fold(0, |acc, fee| acc.saturating_add(fee))
 	}
 
-	fn overage(&self) -> i64 {
-		self.fee() as i64
+	/// Sum of all kernel fees, failing rather than saturating if the sum
+	/// overflows a u64. Use this wherever the total is consensus critical.
+	pub fn checked_fee(&self) -> Result<u64, Error> {
+		self.kernels
+			.iter()
+			.filter_map(|k| match k.features {
+				KernelFeatures::Coinbase => None,
+				KernelFeatures::Plain { fee } | KernelFeatures::HeightLocked { fee, .. } => {
+					Some(fee)
+				}
+			})
+			.try_fold(0u64, |acc, fee| acc.checked_add(fee))
+			.ok_or(Error::FeeOverflow)
+	}
+
+	fn overage(&self) -> Result<i64, Error> {
+		let fee = self.checked_fee()?;
+		if fee > i64::max_value() as u64 {
+			return Err(Error::FeeOverflow);
+		}
+		Ok(fee as i64)
 	}
 
 	/// Calculate transaction weight
@@ -1043,8 +1076,8 @@ impl Transaction {
 		self.body.fee()
 	}
 
-	/// Total overage across all kernels.
-	pub fn overage(&self) -> i64 {
+	/// Total overage across all kernels, failing if the fees overflow.
+	pub fn overage(&self) -> Result<i64, Error> {
 		self.body.overage()
 	}
 
@@ -1074,7 +1107,7 @@ impl Transaction {
 	) -> Result<(), Error> {
 		self.body.validate(weighting, verifier)?;
 		self.body.verify_features()?;
-		self.verify_kernel_sums(self.overage(), self.offset.clone())?;
+		self.verify_kernel_sums(self.overage()?, self.offset.clone())?;
 		Ok(())
 	}
 
@@ -1331,9 +1364,11 @@ impl Input {
 	}
 }
 
-// Enum of various supported kernel "features".
+// Enum of various supported output "features".
 enum_from_primitive! {
-	/// Various flavors of tx kernel.
+	/// Various flavors of tx output.
+	/// The stable set is Plain (0) and Coinbase (1), any other byte is rejected
+	/// on read so a newer output type is never mistaken for an existing one.
 	#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 	#[repr(u8)]
 	pub enum OutputFeatures {
@@ -1353,6 +1388,7 @@ impl Writeable for OutputFeatures {
 
 impl Readable for OutputFeatures {
 	fn read(reader: &mut dyn Reader) -> Result<OutputFeatures, ser::Error> {
+		// Unknown features (from a future version) are corrupted data to us.
 		let features =
 			OutputFeatures::from_u8(reader.read_u8()?).ok_or(ser::Error::CorruptedData)?;
 		Ok(features)
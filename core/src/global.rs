@@ -19,14 +19,15 @@
 use crate::consensus::{
 	graph_weight, valid_header_version, HeaderInfo, BASE_EDGE_BITS, BLOCK_TIME_SEC,
 	COINBASE_MATURITY, CUT_THROUGH_HORIZON, DAY_HEIGHT, DEFAULT_MIN_EDGE_BITS,
-	DIFFICULTY_ADJUST_WINDOW, INITIAL_DIFFICULTY, MAX_BLOCK_WEIGHT, PROOFSIZE,
-	SECOND_POW_EDGE_BITS, STATE_SYNC_THRESHOLD,
+	DIFFICULTY_ADJUST_WINDOW, HALVING_INTERVAL, INITIAL_DIFFICULTY, INITIAL_REWARD,
+	MAX_BLOCK_WEIGHT, PROOFSIZE, SECOND_POW_EDGE_BITS, STATE_SYNC_THRESHOLD,
 };
 use crate::core::block::HeaderVersion;
 use crate::pow::{
 	self, new_cuckaroo_ctx, new_cuckarood_ctx, new_cuckaroom_ctx, new_cuckatoo_ctx, EdgeType,
 	PoWContext,
 };
+use std::cmp::max;
 use util::RwLock;
 
 /// An enum collecting sets of parameters used throughout the
@@ -153,6 +154,11 @@ lazy_static! {
 	/// PoW context type to instantiate
 	pub static ref POW_CONTEXT_TYPE: RwLock<PoWContextTypes> =
 			RwLock::new(PoWContextTypes::Cuckoo);
+
+	/// Reward schedule override as (halving interval, initial reward),
+	/// only honoured under AutomatedTesting
+	pub static ref REWARD_SCHEDULE: RwLock<Option<(u64, u64)>> =
+			RwLock::new(None);
 }
 
 /// Set the mining mode
@@ -161,6 +167,35 @@ pub fn set_mining_mode(mode: ChainTypes) {
 	*param_ref = mode;
 }
 
+/// Override the reward schedule so tests can reach a halving in a handful
+/// of blocks. Ignored unless running AutomatedTesting.
+pub fn set_reward_schedule(halving_interval: u64, initial_reward: u64) {
+	let mut param_ref = REWARD_SCHEDULE.write();
+	*param_ref = Some((halving_interval, initial_reward));
+}
+
+/// Revert to the consensus reward schedule.
+pub fn reset_reward_schedule() {
+	let mut param_ref = REWARD_SCHEDULE.write();
+	*param_ref = None;
+}
+
+/// Number of blocks between reward halvings
+pub fn halving_interval() -> u64 {
+	match (CHAIN_TYPE.read().clone(), *REWARD_SCHEDULE.read()) {
+		(ChainTypes::AutomatedTesting, Some((halving_interval, _))) => max(halving_interval, 1),
+		_ => HALVING_INTERVAL,
+	}
+}
+
+/// Block reward before any halving
+pub fn initial_reward() -> u64 {
+	match (CHAIN_TYPE.read().clone(), *REWARD_SCHEDULE.read()) {
+		(ChainTypes::AutomatedTesting, Some((_, initial_reward))) => initial_reward,
+		_ => INITIAL_REWARD,
+	}
+}
+
 /// Return either a cuckoo context or a cuckatoo context
 /// Single change point
 pub fn create_pow_context<T>(
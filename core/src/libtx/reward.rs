@@ -25,6 +25,8 @@ use keychain::{Identifier, Keychain, SwitchCommitmentType};
 use util::{secp, static_secp_instance};
 
 /// output a reward output
+/// Note: the block reward never drops below NANO_KEPLER (even with zero fees
+/// past the final halving) so the coinbase output value is never zero.
 pub fn output<K, B>(
 	keychain: &K,
 	builder: &B,
@@ -96,12 +96,16 @@ pub fn pow_size(
 ) -> Result<(), Error> {
 	let start_nonce = bh.pow.nonce;
 
+	// only the nonce changes between attempts, so serialize the rest once
+	let mut pre_pow = bh.pre_pow_template();
+
 	// try to find a cuckoo cycle on that header hash
 	loop {
 		// if we found a cycle (not guaranteed) and the proof hash is higher that the
 		// diff, we're all good
 		let mut ctx = global::create_pow_context::<u32>(bh.height, sz, proof_size, MAX_SOLS)?;
-		ctx.set_header_nonce(bh.pre_pow(), None, true)?;
+		BlockHeader::splice_nonce(&mut pre_pow, bh.pow.nonce);
+		ctx.set_header_nonce(pre_pow.clone(), None, true)?;
 		if let Ok(proofs) = ctx.find_cycles() {
 			bh.pow.proof = proofs[0].clone();
 			if bh.pow.to_difficulty(bh.height) >= diff {
@@ -117,6 +121,7 @@ pub fn pow_size(
 		// well)
 		if bh.pow.nonce == start_nonce {
 			bh.timestamp = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(0, 0), Utc);
+			pre_pow = bh.pre_pow_template();
 		}
 	}
 }
@@ -385,6 +385,9 @@ pub fn ser_vec<W: Writeable>(thing: &W, version: ProtocolVersion) -> Result<Vec<
 	Ok(vec)
 }
 
+/// Max number of bytes `BinReader` reads in a single `read_fixed_bytes` call.
+pub const MAX_FIXED_BYTES_READ: usize = 100_000;
+
 /// Utility to read from a binary source
 pub struct BinReader<'a> {
 	source: &'a mut dyn Read,
@@ -432,7 +435,7 @@ impl<'a> Reader for BinReader<'a> {
 	/// Read a fixed number of bytes.
 	fn read_fixed_bytes(&mut self, len: usize) -> Result<Vec<u8>, Error> {
 		// not reading more than 100k bytes in a single read
-		if len > 100_000 {
+		if len > MAX_FIXED_BYTES_READ {
 			return Err(Error::TooLargeReadErr);
 		}
 		let mut buf = vec![0; len];
@@ -14,25 +14,31 @@
 
 mod common;
 use crate::common::{new_block, tx1i2o, tx2i1o, txspend1i1o};
-use crate::core::consensus::BLOCK_OUTPUT_WEIGHT;
+use crate::core::consensus::{self, BLOCK_OUTPUT_WEIGHT};
 use crate::core::core::block::Error;
-use crate::core::core::hash::Hashed;
+use crate::core::core::hash::{Hash, Hashed, ZERO_HASH};
 use crate::core::core::id::ShortIdentifiable;
-use crate::core::core::transaction::{self, Transaction};
+use crate::core::core::transaction::{self, Transaction, TxKernel};
 use crate::core::core::verifier_cache::{LruVerifierCache, VerifierCache};
-use crate::core::core::Committed;
+use crate::core::core::{committed, Committed};
 use crate::core::core::{
-	Block, BlockHeader, CompactBlock, HeaderVersion, KernelFeatures, OutputFeatures,
+	Block, BlockHeader, CompactBlock, HeaderFieldset, HeaderVersion, Input, KernelFeatures, Output,
+	OutputFeatures, ValidationMetrics,
 };
 use crate::core::libtx::build::{self, input, output};
-use crate::core::libtx::ProofBuilder;
-use crate::core::{global, ser};
+use crate::core::libtx::{reward, ProofBuilder};
+use crate::core::pow::{Difficulty, Proof};
+use crate::core::{genesis, global, ser};
+use chrono::prelude::{DateTime, NaiveDateTime, Utc};
 use chrono::Duration;
 use kepler_core as core;
 use kepler_core::global::ChainTypes;
-use keychain::{BlindingFactor, ExtKeychain, Keychain};
+use keychain::{BlindingFactor, ExtKeychain, Keychain, SwitchCommitmentType};
+use std::collections::HashSet;
 use std::sync::Arc;
 use util::secp;
+use util::secp::pedersen::{Commitment, RangeProof};
+use util::secp::Signature;
 use util::RwLock;
 
 fn verifier_cache() -> Arc<RwLock<dyn VerifierCache>> {
@@ -119,6 +125,85 @@ fn block_with_cut_through() {
 	assert_eq!(b.outputs().len(), 3);
 }
 
+#[test]
+// cut_through moves the body out of the block, check it matches cutting
+// through a cloned copy of the same inputs and outputs
+fn block_cut_through_matches_cloned() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let key_id1 = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let key_id2 = ExtKeychain::derive_key_id(1, 2, 0, 0, 0);
+	let key_id3 = ExtKeychain::derive_key_id(1, 3, 0, 0, 0);
+
+	let btx1 = build::transaction(
+		KernelFeatures::Plain { fee: 2 },
+		vec![input(7, key_id1), output(5, key_id2.clone())],
+		&keychain,
+		&builder,
+	)
+	.unwrap();
+	let btx2 = txspend1i1o(5, &keychain, &builder, key_id2, key_id3);
+
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+	for tx in &[btx1, btx2] {
+		b.inputs_mut().extend_from_slice(tx.inputs());
+		b.outputs_mut().extend_from_slice(tx.outputs());
+		b.kernels_mut().extend_from_slice(tx.kernels());
+	}
+	assert_eq!(b.inputs().len(), 2);
+	assert_eq!(b.outputs().len(), 3);
+
+	let mut inputs = b.inputs().clone();
+	let mut outputs = b.outputs().clone();
+	transaction::cut_through(&mut inputs, &mut outputs).unwrap();
+	let expected =
+		transaction::TransactionBody::init(inputs, outputs, b.kernels().clone(), false).unwrap();
+
+	let header = b.header.clone();
+	let b = b.cut_through().unwrap();
+	assert_eq!(b.header, header);
+	assert_eq!(b.inputs(), &expected.inputs);
+	assert_eq!(b.outputs(), &expected.outputs);
+	assert_eq!(b.kernels(), &expected.kernels);
+	assert_eq!(b.inputs().len(), 1);
+	assert_eq!(b.outputs().len(), 2);
+}
+
+#[test]
+fn block_cut_through_report() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let key_id1 = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let key_id2 = ExtKeychain::derive_key_id(1, 2, 0, 0, 0);
+	let key_id3 = ExtKeychain::derive_key_id(1, 3, 0, 0, 0);
+
+	let btx1 = build::transaction(
+		KernelFeatures::Plain { fee: 2 },
+		vec![input(7, key_id1), output(5, key_id2.clone())],
+		&keychain,
+		&builder,
+	)
+	.unwrap();
+	let btx2 = txspend1i1o(5, &keychain, &builder, key_id2, key_id3);
+	let intermediate = btx1.outputs()[0].commitment();
+
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+	for tx in &[btx1, btx2] {
+		b.inputs_mut().extend_from_slice(tx.inputs());
+		b.outputs_mut().extend_from_slice(tx.outputs());
+		b.kernels_mut().extend_from_slice(tx.kernels());
+	}
+
+	let (b, eliminated) = b.cut_through_report().unwrap();
+	assert_eq!(eliminated, vec![intermediate]);
+	assert!(b.outputs().iter().all(|o| o.commitment() != intermediate));
+	assert!(b.inputs().iter().all(|i| i.commitment() != intermediate));
+}
+
 #[test]
 fn empty_block_with_coinbase_is_valid() {
 	let keychain = ExtKeychain::from_random_seed(false).unwrap();
@@ -403,6 +488,25 @@ fn compact_block_hash_with_nonce() {
 	);
 }
 
+#[test]
+fn compact_block_with_fixed_nonce() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let tx = tx1i2o();
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(vec![&tx], &keychain, &builder, &prev, &key_id);
+	let cb1 = CompactBlock::from_block_with_nonce(b.clone(), 42);
+	let cb2 = CompactBlock::from_block_with_nonce(b.clone(), 42);
+
+	assert_eq!(cb1.nonce, 42);
+	assert_eq!(cb1.kern_ids(), cb2.kern_ids());
+	assert_eq!(cb1.kern_ids()[0], tx.kernels()[0].short_id(&b.hash(), 42));
+
+	let cb3 = CompactBlock::from_block_with_nonce(b, 43);
+	assert!(cb1.kern_ids()[0] != cb3.kern_ids()[0]);
+}
+
 #[test]
 fn convert_block_to_compact_block() {
 	let keychain = ExtKeychain::from_random_seed(false).unwrap();
@@ -427,6 +531,35 @@ fn convert_block_to_compact_block() {
 	);
 }
 
+#[test]
+fn compact_block_keep_full() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let tx1 = tx1i2o();
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(vec![&tx1], &keychain, &builder, &prev, &key_id);
+
+	// Nothing kept full, same as the default conversion.
+	let cb = b.to_compact_block(&HashSet::new());
+	assert_eq!(cb.out_full().len(), 1);
+	assert_eq!(cb.kern_full().len(), 1);
+	assert_eq!(cb.kern_ids().len(), 1);
+
+	// Keep one of the (non-coinbase) tx outputs in full, the tx kernel is
+	// still sent as a short_id so the tx (and its inputs) can be looked up.
+	let out = tx1.outputs()[0].clone();
+	let keep_full: HashSet<_> = vec![out.commitment()].into_iter().collect();
+	let cb = b.to_compact_block(&keep_full);
+	assert_eq!(cb.out_full().len(), 2);
+	assert!(cb.out_full().contains(&out));
+	assert_eq!(cb.kern_full().len(), 1);
+	assert_eq!(
+		cb.kern_ids(),
+		&vec![tx1.kernels()[0].short_id(&cb.hash(), cb.nonce)]
+	);
+}
+
 #[test]
 fn hydrate_empty_compact_block() {
 	let keychain = ExtKeychain::from_random_seed(false).unwrap();
@@ -594,3 +727,672 @@ fn validate_header_proof() {
 	)
 	.is_err());
 }
+
+#[test]
+fn header_proof_wrong_size() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+	let pre_pow = util::to_hex(b.header.pre_pow());
+	let pre_pow = pre_pow[..pre_pow.len() - 16].to_string();
+
+	for size in &[global::proofsize() - 1, global::proofsize() + 1] {
+		let proof = Proof::new((0..*size as u64).collect());
+		assert_eq!(
+			BlockHeader::from_pre_pow_and_proof(pre_pow.clone(), b.header.pow.nonce, proof),
+			Err(Error::InvalidPow)
+		);
+	}
+}
+
+#[test]
+fn pre_pow_template_splice_nonce() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+
+	let template = b.header.pre_pow_template();
+	assert_eq!(b.header.pre_pow_nonce_offset(), 238);
+	assert_eq!(template.len(), b.header.pre_pow_nonce_offset() + 8);
+
+	for nonce in vec![0, 1, 28106, u64::max_value()] {
+		b.header.pow.nonce = nonce;
+		let mut buf = template.clone();
+		BlockHeader::splice_nonce(&mut buf, nonce);
+		assert_eq!(buf, b.header.pre_pow());
+
+		// splicing into a buffer that already carries a nonce replaces it
+		BlockHeader::splice_nonce(&mut buf, nonce.wrapping_add(1));
+		BlockHeader::splice_nonce(&mut buf, nonce);
+		assert_eq!(buf, b.header.pre_pow());
+	}
+}
+
+#[test]
+#[should_panic(expected = "pre-pow buffer too short")]
+fn splice_nonce_short_buffer() {
+	let mut buf = vec![0; 7];
+	BlockHeader::splice_nonce(&mut buf, 1);
+}
+
+#[test]
+fn miner_subsidy_matches_coinbase() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let tx1 = tx1i2o();
+	let tx2 = tx2i1o();
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(vec![&tx1, &tx2], &keychain, &builder, &prev, &key_id);
+
+	assert_eq!(b.total_fees(), Ok(4));
+	assert_eq!(b.miner_subsidy(), Ok(consensus::reward(1, 4)));
+
+	let coinbase_commit = keychain
+		.commit(
+			b.miner_subsidy().unwrap(),
+			&key_id,
+			SwitchCommitmentType::Regular,
+		)
+		.unwrap();
+	let coinbase = b.outputs().iter().find(|out| out.is_coinbase()).unwrap();
+	assert_eq!(coinbase.commitment(), coinbase_commit);
+	assert!(b.verify_coinbase().is_ok());
+}
+
+#[test]
+fn block_error_with_context() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+
+	let err = Error::CoinbaseSumMismatch.with_context(b.hash(), b.header.height);
+	assert_eq!(
+		err,
+		Error::Context {
+			block_hash: b.hash(),
+			height: 1,
+			error: Box::new(Error::CoinbaseSumMismatch),
+		}
+	);
+
+	let msg = format!("{}", err);
+	assert!(msg.contains("at height 1"));
+	assert!(msg.contains(&b.hash().to_string()));
+	assert!(msg.contains("CoinbaseSumMismatch"));
+}
+
+#[test]
+fn verify_pow_random_proof() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+
+	// Block::new sets a random (invalid) proof on the header.
+	let b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+	assert_eq!(b.verify_pow(&prev), Err(Error::InvalidPow));
+}
+
+#[test]
+fn block_header_diff() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+
+	let header1 = b.header.clone();
+	assert!(header1.diff(&b.header).is_empty());
+
+	let mut header2 = b.header.clone();
+	header2.output_root = header1.hash();
+	let diffs = header1.diff(&header2);
+	assert_eq!(diffs.len(), 1);
+	assert_eq!(diffs[0].field, "output_root");
+	assert_eq!(diffs[0].old, header1.output_root.to_hex());
+	assert_eq!(diffs[0].new, header1.hash().to_hex());
+
+	// Headers differing only in the extra commitment are reported too.
+	let mut header3 = b.header.clone();
+	header3.version = HeaderVersion(4);
+	let mut header4 = header3.clone();
+	header4.extra_commitment = prev.hash();
+	let diffs = header3.diff(&header4);
+	assert_eq!(diffs.len(), 1);
+	assert_eq!(diffs[0].field, "extra_commitment");
+	assert_eq!(diffs[0].old, ZERO_HASH.to_hex());
+	assert_eq!(diffs[0].new, prev.hash().to_hex());
+}
+
+#[test]
+fn block_header_extra_commitment() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+
+	// Not serialized before header version 4.
+	let mut header = b.header.clone();
+	header.extra_commitment = prev.hash();
+	assert_eq!(header.pre_pow(), b.header.pre_pow());
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &header).expect("serialization failed");
+	let header2: BlockHeader = ser::deserialize_default(&mut &vec[..]).unwrap();
+	assert_eq!(header2.extra_commitment, ZERO_HASH);
+
+	// From version 4 the commitment round-trips and is part of the pre-pow,
+	// so it is committed to by the proof (and in turn the header hash).
+	let mut header = b.header.clone();
+	header.version = HeaderVersion(4);
+	let pre_pow_without = header.pre_pow();
+	assert_eq!(header.pre_pow_nonce_offset(), 270);
+	assert_eq!(pre_pow_without.len(), 278);
+
+	// An unused (zero) commitment round-trips as is.
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &header).expect("serialization failed");
+	let header2: BlockHeader = ser::deserialize_default(&mut &vec[..]).unwrap();
+	assert_eq!(header2, header);
+
+	header.extra_commitment = prev.hash();
+	assert_ne!(header.pre_pow(), pre_pow_without);
+
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &header).expect("serialization failed");
+	let header2: BlockHeader = ser::deserialize_default(&mut &vec[..]).unwrap();
+	assert_eq!(header2.extra_commitment, prev.hash());
+	assert_eq!(header2, header);
+
+	// Setting it does not affect any of the sums.
+	let mut b = b;
+	b.header.extra_commitment = prev.hash();
+	assert!(b.verify_coinbase().is_ok());
+}
+
+// Fixed serialization of a fully specified header and a body with one
+// input, one output and one kernel built from fixed bytes. Any change to
+// field order or encoding breaks this vector, unlike the serialized size
+// tests above which only catch length changes.
+const BLOCK_TEST_VECTOR: &str = concat!(
+	"0002000000000000002a000000005f5e10000101010101010101010101010101",
+	"0101010101010101010101010101010101010202020202020202020202020202",
+	"0202020202020202020202020202020202020303030303030303030303030303",
+	"0303030303030303030303030303030303030404040404040404040404040404",
+	"0404040404040404040404040404040404040505050505050505050505050505",
+	"0505050505050505050505050505050505050606060606060606060606060606",
+	"0606060606060606060606060606060606060000000000000007000000000000",
+	"000800000000000003e80000000901020304050607080a010830000105187000",
+	"0200000000000000010000000000000001000000000000000100080808080808",
+	"0808080808080808080808080808080808080808080808080808080009090909",
+	"0909090909090909090909090909090909090909090909090909090909000000",
+	"00000002a30a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+	"0a0a0a0a0a0a0a0a00000000000000000d0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+	"0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0c0c0c0c0c0c0c0c0c0c0c0c0c0c",
+	"0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c",
+	"0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c",
+);
+
+#[test]
+fn block_serialization_test_vector() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let mut header = BlockHeader::default();
+	header.version = HeaderVersion(2);
+	header.height = 42;
+	header.timestamp =
+		DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(1_600_000_000, 0), Utc);
+	header.prev_hash = Hash::from_vec(&[1; 32]);
+	header.prev_root = Hash::from_vec(&[2; 32]);
+	header.output_root = Hash::from_vec(&[3; 32]);
+	header.range_proof_root = Hash::from_vec(&[4; 32]);
+	header.kernel_root = Hash::from_vec(&[5; 32]);
+	header.total_kernel_offset = BlindingFactor::from_slice(&[6; 32]);
+	header.output_mmr_size = 7;
+	header.kernel_mmr_size = 8;
+	header.pow.total_difficulty = Difficulty::from_num(1_000);
+	header.pow.secondary_scaling = 9;
+	header.pow.nonce = 0x0102_0304_0506_0708;
+	header.pow.proof = Proof::new((1..=8).collect());
+	let mut b = Block::with_header(header);
+	b.inputs_mut().push(Input::new(
+		OutputFeatures::Plain,
+		Commitment::from_vec(vec![8; 33]),
+	));
+	b.outputs_mut().push(Output {
+		features: OutputFeatures::Plain,
+		commit: Commitment::from_vec(vec![9; 33]),
+		proof: RangeProof {
+			proof: [10; secp::constants::MAX_PROOF_SIZE],
+			plen: secp::constants::MAX_PROOF_SIZE,
+		},
+	});
+	b.kernels_mut().push(TxKernel {
+		features: KernelFeatures::Plain { fee: 13 },
+		excess: Commitment::from_vec(vec![11; 33]),
+		excess_sig: Signature::from_raw_data(&[12; 64]).unwrap(),
+	});
+
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &b).expect("serialization failed");
+	assert_eq!(util::to_hex(vec.clone()), BLOCK_TEST_VECTOR);
+
+	let b2: Block = ser::deserialize_default(&mut &vec[..]).unwrap();
+	assert_eq!(b2.header, b.header);
+	assert_eq!(b2.inputs(), b.inputs());
+	assert_eq!(b2.outputs(), b.outputs());
+	assert_eq!(b2.kernels(), b.kernels());
+}
+
+#[test]
+fn verify_timestamp() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+	assert!(b.verify_timestamp(&prev).is_ok());
+
+	// Same timestamp as the parent.
+	b.header.timestamp = prev.timestamp;
+	assert_eq!(b.verify_timestamp(&prev), Err(Error::InvalidBlockTime));
+
+	// Timestamped before the parent.
+	let mut later_prev = prev.clone();
+	later_prev.timestamp = Utc::now();
+	b.header.timestamp = later_prev.timestamp - Duration::seconds(1);
+	assert_eq!(
+		b.verify_timestamp(&later_prev),
+		Err(Error::InvalidBlockTime)
+	);
+
+	// Too far in the future.
+	b.header.timestamp = Utc::now() + Duration::seconds(13 * consensus::BLOCK_TIME_SEC as i64);
+	assert_eq!(b.verify_timestamp(&prev), Err(Error::InvalidBlockTime));
+}
+
+#[test]
+fn read_framed_blocks() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id1 = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let key_id2 = ExtKeychain::derive_key_id(1, 2, 0, 0, 0);
+	let mut tx1 = tx1i2o();
+	let b1 = new_block(vec![&mut tx1], &keychain, &builder, &prev, &key_id1);
+	let b2 = new_block(vec![], &keychain, &builder, &b1.header, &key_id2);
+
+	let mut stream = vec![];
+	for b in &[&b1, &b2] {
+		let mut vec = Vec::new();
+		ser::serialize_default(&mut vec, *b).expect("serialization failed");
+		ser::serialize_default(&mut stream, &(vec.len() as u64)).unwrap();
+		stream.extend(vec);
+	}
+
+	let mut source = &stream[..];
+	{
+		let mut reader = ser::BinReader::new(&mut source, ser::ProtocolVersion::local());
+		let read1 = Block::read_framed(&mut reader).unwrap();
+		let read2 = Block::read_framed(&mut reader).unwrap();
+		assert_eq!(read1.hash(), b1.hash());
+		assert_eq!(read1.kernels(), b1.kernels());
+		assert_eq!(read2.hash(), b2.hash());
+		assert_eq!(read2.kernels(), b2.kernels());
+	}
+	assert!(source.is_empty());
+
+	// A frame longer than the block it contains is rejected.
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &b2).expect("serialization failed");
+	vec.push(0);
+	let mut framed = vec![];
+	ser::serialize_default(&mut framed, &(vec.len() as u64)).unwrap();
+	framed.extend(vec);
+	let res: Result<Block, _> = {
+		let mut source = &framed[..];
+		let mut reader = ser::BinReader::new(&mut source, ser::ProtocolVersion::local());
+		Block::read_framed(&mut reader)
+	};
+	assert_eq!(res.unwrap_err(), ser::Error::CorruptedData);
+
+	// As is a length prefix over the max block size.
+	let mut framed = vec![];
+	ser::serialize_default(&mut framed, &(Block::max_serialized_size() + 1)).unwrap();
+	let res: Result<Block, _> = {
+		let mut source = &framed[..];
+		let mut reader = ser::BinReader::new(&mut source, ser::ProtocolVersion::local());
+		Block::read_framed(&mut reader)
+	};
+	assert_eq!(res.unwrap_err(), ser::Error::TooLargeReadErr);
+}
+
+#[test]
+fn read_framed_input_heavy_block() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+
+	// Fill the rest of the block weight with inputs.
+	let coinbase_weight = BLOCK_OUTPUT_WEIGHT + consensus::BLOCK_KERNEL_WEIGHT;
+	let max_inputs = global::max_block_weight() - coinbase_weight;
+	let switch = SwitchCommitmentType::Regular;
+	for n in 0..max_inputs {
+		let key_id = ExtKeychain::derive_key_id(1, n as u32 + 2, 0, 0, 0);
+		let commit = keychain.commit(5, &key_id, switch).unwrap();
+		b.inputs_mut()
+			.push(Input::new(OutputFeatures::Plain, commit));
+	}
+	b.inputs_mut().sort_unstable();
+	b.validate_read().unwrap();
+
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &b).expect("serialization failed");
+	assert!(vec.len() as u64 <= Block::max_serialized_size());
+
+	let mut framed = vec![];
+	ser::serialize_default(&mut framed, &(vec.len() as u64)).unwrap();
+	framed.extend(vec);
+	let mut source = &framed[..];
+	let mut reader = ser::BinReader::new(&mut source, ser::ProtocolVersion::local());
+	let read = Block::read_framed(&mut reader).unwrap();
+	assert_eq!(read.hash(), b.hash());
+	assert_eq!(read.inputs(), b.inputs());
+}
+
+#[test]
+fn block_header_json_round_trip() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+	b.header.extra_commitment = prev.hash();
+
+	let json = serde_json::to_string(&b.header).unwrap();
+	let header: BlockHeader = serde_json::from_str(&json).unwrap();
+	assert_eq!(header, b.header);
+	assert_eq!(header.total_kernel_offset, b.header.total_kernel_offset);
+}
+
+#[test]
+fn validate_with_sums() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut tx1 = tx2i1o();
+	let b = new_block(vec![&mut tx1], &keychain, &builder, &prev, &key_id);
+
+	let (utxo_sum, kernel_sum) = b
+		.validate_with_sums(&BlindingFactor::zero(), verifier_cache())
+		.unwrap();
+	assert_eq!(
+		kernel_sum,
+		b.validate(&BlindingFactor::zero(), verifier_cache())
+			.unwrap()
+	);
+
+	// The block overage (the reward) is negative so counts against the outputs.
+	let over_commit = {
+		let secp = util::static_secp_instance();
+		let secp = secp.lock();
+		secp.commit_value(consensus::reward(b.header.height, 0))
+			.unwrap()
+	};
+	let mut negative = b.inputs_committed();
+	negative.push(over_commit);
+	let expected = committed::sum_commits_slices(&b.outputs_committed(), &negative).unwrap();
+	assert_eq!(utxo_sum, expected);
+}
+
+#[test]
+fn read_header_timestamp_bounds() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &BlockHeader::default()).expect("serialization failed");
+
+	// timestamp follows the u16 version and u64 height
+	let read_with_timestamp = |timestamp: i64| -> Result<BlockHeader, ser::Error> {
+		let mut vec = vec.clone();
+		vec[10..18].copy_from_slice(&timestamp.to_be_bytes());
+		ser::deserialize_default(&mut &vec[..])
+	};
+
+	let max = chrono::naive::MAX_DATE.and_hms(0, 0, 0).timestamp();
+	let min = chrono::naive::MIN_DATE.and_hms(0, 0, 0).timestamp();
+	assert_eq!(read_with_timestamp(max).unwrap().timestamp.timestamp(), max);
+	assert_eq!(read_with_timestamp(min).unwrap().timestamp.timestamp(), min);
+
+	// Out of range values must be rejected, not panic converting to a date.
+	for timestamp in &[max + 1, min - 1, i64::MAX, i64::MIN] {
+		assert_eq!(
+			read_with_timestamp(*timestamp).unwrap_err(),
+			ser::Error::CorruptedData
+		);
+	}
+}
+
+#[test]
+fn block_header_next_skeleton() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+
+	let header = b.header.next_skeleton(Difficulty::from_num(7));
+	assert_eq!(header.prev_hash, b.hash());
+	assert_eq!(header.height, b.header.height + 1);
+	assert_eq!(header.version, consensus::header_version(header.height));
+	assert_eq!(
+		header.total_difficulty(),
+		b.header.total_difficulty() + Difficulty::from_num(7)
+	);
+	assert_eq!(header.total_kernel_offset, b.header.total_kernel_offset);
+}
+
+#[test]
+fn validate_with_metrics() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut tx1 = tx2i1o();
+	let b = new_block(vec![&mut tx1], &keychain, &builder, &prev, &key_id);
+
+	let mut metrics = ValidationMetrics::default();
+	b.validate_with_metrics(&BlindingFactor::zero(), verifier_cache(), &mut metrics)
+		.unwrap();
+	assert!(metrics.body > std::time::Duration::from_secs(0));
+	assert!(metrics.kernel_sums > std::time::Duration::from_secs(0));
+}
+
+#[test]
+fn header_fieldset_by_version() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	assert!(!HeaderFieldset::for_version(HeaderVersion(3)).extra_commitment);
+	assert!(HeaderFieldset::for_version(HeaderVersion(4)).extra_commitment);
+
+	let mut header = BlockHeader::default();
+	header.extra_commitment = Hash::from_vec(&[1; 32]);
+	for version in &[HeaderVersion(3), HeaderVersion(4)] {
+		header.version = *version;
+		let fields = header.fieldset();
+		let mut vec = Vec::new();
+		ser::serialize_default(&mut vec, &header).expect("serialization failed");
+		let mut base = header.clone();
+		base.version = HeaderVersion(1);
+		let mut base_vec = Vec::new();
+		ser::serialize_default(&mut base_vec, &base).expect("serialization failed");
+		assert_eq!(vec.len(), base_vec.len() + fields.pre_pow_len());
+
+		let header2: BlockHeader = ser::deserialize_default(&mut &vec[..]).unwrap();
+		assert_eq!(
+			header2.extra_commitment != ZERO_HASH,
+			fields.extra_commitment
+		);
+	}
+}
+
+#[test]
+fn block_body_iterators() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut tx1 = tx2i1o();
+	let b = new_block(vec![&mut tx1], &keychain, &builder, &prev, &key_id);
+
+	assert_eq!(
+		b.inputs_iter().collect::<Vec<_>>(),
+		b.inputs().iter().collect::<Vec<_>>()
+	);
+	assert_eq!(
+		b.outputs_iter().collect::<Vec<_>>(),
+		b.outputs().iter().collect::<Vec<_>>()
+	);
+	assert_eq!(
+		b.kernels_iter().collect::<Vec<_>>(),
+		b.kernels().iter().collect::<Vec<_>>()
+	);
+	assert_eq!(b.inputs_iter().count(), 2);
+	assert_eq!(b.outputs_iter().count(), 2);
+	assert_eq!(b.kernels_iter().count(), 2);
+}
+
+#[test]
+fn total_fees_overflow() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let mut b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+	for _ in 0..2 {
+		b.kernels_mut()
+			.push(TxKernel::with_features(KernelFeatures::Plain {
+				fee: u64::MAX / 2 + 1,
+			}));
+	}
+
+	let overflow = Err(Error::Transaction(transaction::Error::FeeOverflow));
+	assert_eq!(b.total_fees(), overflow);
+	assert_eq!(b.miner_subsidy(), overflow);
+	assert_eq!(
+		b.verify_coinbase(),
+		Err(Error::Transaction(transaction::Error::FeeOverflow))
+	);
+}
+
+#[test]
+fn corrupted_total_kernel_offset() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let tx = tx1i2o();
+	let mut b = new_block(vec![&tx], &keychain, &builder, &prev, &key_id);
+	b.validate(&prev.total_kernel_offset, verifier_cache())
+		.unwrap();
+
+	// The block offset is derived as header total less prev total, so a header
+	// total that is not prev plus the tx offsets breaks the kernel sums.
+	b.header.total_kernel_offset = BlindingFactor::from_slice(&[7; 32]);
+	match b.validate(&prev.total_kernel_offset, verifier_cache()) {
+		Err(Error::Committed(committed::Error::KernelSumMismatchWithSums(
+			utxo_sum,
+			kernel_sum,
+		))) => {
+			// The utxo sum is unaffected, only the kernel sum plus offset differs.
+			assert_eq!(utxo_sum, b.sum_commitments(b.header.overage()).unwrap());
+			assert!(utxo_sum != kernel_sum);
+		}
+		res => panic!("expected kernel sum mismatch, got {:?}", res),
+	}
+}
+
+#[test]
+fn post_emission_coinbase_is_valid() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let mut prev = BlockHeader::default();
+	prev.height = 64 * global::halving_interval();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+
+	// Past the final halving an empty block still pays the minimum reward.
+	assert_eq!(
+		consensus::reward(prev.height + 1, 0),
+		consensus::NANO_KEPLER
+	);
+
+	let b = new_block(vec![], &keychain, &builder, &prev, &key_id);
+	assert_eq!(b.outputs().len(), 1);
+	assert_eq!(b.miner_subsidy(), Ok(consensus::NANO_KEPLER));
+	b.validate(&prev.total_kernel_offset, verifier_cache())
+		.unwrap();
+}
+
+#[test]
+fn validate_genesis() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let gen = genesis::genesis_dev();
+
+	// The dev genesis has no reward so the standard coinbase rules reject it.
+	assert!(gen
+		.validate(&BlindingFactor::zero(), verifier_cache())
+		.is_err());
+	gen.validate_genesis(verifier_cache()).unwrap();
+
+	// A genesis block with a reward still has its coinbase verified.
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let key_id = ExtKeychain::derive_key_id(1, 0, 0, 0, 0);
+	let (out, kern) = reward::output(&keychain, &builder, &key_id, 0, 0, false).unwrap();
+	let gen_with_reward = gen.clone().with_reward(out, kern);
+	gen_with_reward.validate_genesis(verifier_cache()).unwrap();
+
+	let mut not_genesis = gen;
+	not_genesis.header.height = 1;
+	assert!(not_genesis.validate_genesis(verifier_cache()).is_err());
+}
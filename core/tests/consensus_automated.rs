@@ -13,8 +13,8 @@
 
 use chrono::Utc;
 use kepler_core::consensus::{
-	next_difficulty, HeaderInfo, AR_SCALE_DAMP_FACTOR, BLOCK_TIME_SEC, DIFFICULTY_ADJUST_WINDOW,
-	MIN_DIFFICULTY,
+	next_difficulty, reward, HeaderInfo, AR_SCALE_DAMP_FACTOR, BLOCK_TIME_SEC,
+	DIFFICULTY_ADJUST_WINDOW, INITIAL_REWARD, MIN_DIFFICULTY,
 };
 use kepler_core::global;
 use kepler_core::pow::Difficulty;
@@ -156,3 +156,18 @@ fn repeat_offs(from: u64, interval: u64, diff: u64, len: u64) -> Vec<HeaderInfo>
 		Some(from),
 	)
 }
+
+/// Checks the reward schedule can be shortened for testing halvings
+#[test]
+fn reward_schedule_override() {
+	global::set_mining_mode(global::ChainTypes::AutomatedTesting);
+	global::set_reward_schedule(4, 1_000);
+	assert_eq!(reward(1, 0), 1_000);
+	assert_eq!(reward(3, 0), 1_000);
+	assert_eq!(reward(4, 0), 500);
+	assert_eq!(reward(5, 7), 507);
+	assert_eq!(reward(8, 0), 250);
+
+	global::reset_reward_schedule();
+	assert_eq!(reward(5, 0), INITIAL_REWARD);
+}
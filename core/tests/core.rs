@@ -21,14 +21,15 @@ use self::core::core::block::Error::KernelLockHeight;
 use self::core::core::hash::{Hashed, ZERO_HASH};
 use self::core::core::verifier_cache::{LruVerifierCache, VerifierCache};
 use self::core::core::{
-	aggregate, deaggregate, KernelFeatures, Output, Transaction, TxKernel, Weighting,
+	aggregate, committed, deaggregate, transaction, KernelFeatures, Output, Transaction, TxKernel,
+	Weighting,
 };
 use self::core::libtx::build::{self, initial_tx, input, output, with_excess};
 use self::core::libtx::ProofBuilder;
 use self::core::ser;
 use crate::common::{new_block, tx1i1o, tx1i2o, tx2i1o};
 use kepler_core as core;
-use keychain::{BlindingFactor, ExtKeychain, Keychain};
+use keychain::{BlindingFactor, ExtKeychain, Keychain, SwitchCommitmentType};
 use std::sync::Arc;
 use util::static_secp_instance;
 use util::RwLock;
@@ -592,3 +593,37 @@ pub fn test_verify_2i1o_sig() {
 	tx.validate(Weighting::AsTransaction, verifier_cache())
 		.unwrap();
 }
+
+#[test]
+fn overage_fee_overflow() {
+	let kernel = |fee| TxKernel::with_features(KernelFeatures::Plain { fee });
+	let tx = Transaction::empty().with_kernel(kernel(i64::max_value() as u64));
+	assert_eq!(tx.overage(), Ok(i64::max_value()));
+
+	let tx = tx.with_kernel(kernel(1));
+	assert_eq!(tx.overage(), Err(transaction::Error::FeeOverflow));
+
+	let tx = Transaction::empty()
+		.with_kernel(kernel(u64::max_value()))
+		.with_kernel(kernel(1));
+	assert_eq!(tx.overage(), Err(transaction::Error::FeeOverflow));
+}
+
+#[test]
+fn sum_commitments_cancels_out() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let key_id1 = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let key_id2 = ExtKeychain::derive_key_id(1, 2, 0, 0, 0);
+	let a = keychain
+		.commit(5, &key_id1, SwitchCommitmentType::Regular)
+		.unwrap();
+	let b = keychain
+		.commit(7, &key_id2, SwitchCommitmentType::Regular)
+		.unwrap();
+
+	assert_eq!(committed::sum_commits_slices(&[a, b], &[b]).unwrap(), a);
+	assert_eq!(
+		committed::sum_commits_slices(&[a, b], &[]).unwrap(),
+		committed::sum_commits(vec![a, b], vec![]).unwrap()
+	);
+}
@@ -16,7 +16,8 @@
 
 pub mod common;
 
-use self::core::core::{Output, OutputFeatures};
+use self::core::core::{Output, OutputFeatures, TransactionBody};
+use self::core::global::{self, ChainTypes};
 use self::core::libtx::proof;
 use self::core::ser;
 use kepler_core as core;
@@ -45,3 +46,67 @@ fn test_output_ser_deser() {
 	assert_eq!(dout.commit, out.commit);
 	assert_eq!(dout.proof, out.proof);
 }
+
+#[test]
+fn test_output_unknown_features() {
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let switch = keychain::SwitchCommitmentType::Regular;
+	let commit = keychain.commit(5, &key_id, switch).unwrap();
+	let builder = proof::ProofBuilder::new(&keychain);
+	let proof = proof::create(&keychain, &builder, 5, &key_id, switch, commit, None).unwrap();
+
+	let out = Output {
+		features: OutputFeatures::Coinbase,
+		commit: commit,
+		proof: proof,
+	};
+
+	let mut vec = vec![];
+	ser::serialize_default(&mut vec, &out).expect("serialized failed");
+	assert_eq!(vec[0], OutputFeatures::Coinbase as u8);
+
+	// The features byte comes first, anything beyond the known set is rejected.
+	for unknown in [2u8, 3, 0xff].iter() {
+		vec[0] = *unknown;
+		let res: Result<Output, _> = ser::deserialize_default(&mut &vec[..]);
+		assert_eq!(res.unwrap_err(), ser::Error::CorruptedData);
+	}
+}
+
+#[test]
+fn test_body_read_oversized_counts() {
+	global::set_mining_mode(ChainTypes::AutomatedTesting);
+	let max_weight = global::max_block_weight() as u64;
+
+	let read_counts = |counts: [u64; 3]| -> Result<TransactionBody, ser::Error> {
+		let mut vec = vec![];
+		for count in counts.iter() {
+			ser::serialize_default(&mut vec, count).unwrap();
+		}
+		ser::deserialize_default(&mut &vec[..])
+	};
+
+	// A huge count must be rejected up front rather than attempting to read
+	// (or allocate for) that many entries.
+	assert_eq!(
+		read_counts([u64::MAX, 0, 0]).unwrap_err(),
+		ser::Error::TooLargeReadErr
+	);
+	assert_eq!(
+		read_counts([1 << 32, 0, 0]).unwrap_err(),
+		ser::Error::TooLargeReadErr
+	);
+	assert_eq!(
+		read_counts([0, max_weight, 0]).unwrap_err(),
+		ser::Error::TooLargeReadErr
+	);
+	assert_eq!(
+		read_counts([0, 0, max_weight]).unwrap_err(),
+		ser::Error::TooLargeReadErr
+	);
+
+	// Counts within bounds get as far as reading the (missing) entries.
+	assert_eq!(read_counts([1, 0, 0]).unwrap_err(), ser::Error::CountError);
+	assert!(read_counts([0, 0, 0]).is_ok());
+}
@@ -16,7 +16,7 @@
 //! Used for both the txpool and stempool layers in the pool.
 
 use self::core::core::hash::{Hash, Hashed};
-use self::core::core::id::{ShortId, ShortIdentifiable};
+use self::core::core::id::{ShortId, ShortIdHasher};
 use self::core::core::transaction;
 use self::core::core::verifier_cache::VerifierCache;
 use self::core::core::{
@@ -55,22 +55,22 @@ impl Pool {
 
 	/// Does the transaction pool contain an entry for the given transaction?
 	pub fn contains_tx(&self, hash: Hash) -> bool {
-		self.entries.iter().any(|x| x.tx.hash() == hash)
+		self.entries.iter().any(|x| x.tx().hash() == hash)
 	}
 
 	pub fn get_tx(&self, hash: Hash) -> Option<Transaction> {
 		self.entries
 			.iter()
-			.find(|x| x.tx.hash() == hash)
-			.map(|x| x.tx.clone())
+			.find(|x| x.tx().hash() == hash)
+			.map(|x| x.tx().clone())
 	}
 
 	/// Query the tx pool for an individual tx matching the given kernel hash.
 	pub fn retrieve_tx_by_kernel_hash(&self, hash: Hash) -> Option<Transaction> {
 		for x in &self.entries {
-			for k in x.tx.kernels() {
+			for k in x.tx().kernels() {
 				if k.hash() == hash {
-					return Some(x.tx.clone());
+					return Some(x.tx().clone());
 				}
 			}
 		}
@@ -90,13 +90,17 @@ impl Pool {
 		let mut txs = vec![];
 		let mut found_ids = vec![];
 
+		// Derive the siphasher keys for this block hash and nonce once, and
+		// reuse the kernel hashes cached on each pool entry.
+		let hasher = ShortIdHasher::new(&hash, nonce);
+
 		// Rehash all entries in the pool using short_ids based on provided hash and nonce.
 		'outer: for x in &self.entries {
-			for k in x.tx.kernels() {
+			for kernel_hash in x.kernel_hashes() {
 				// rehash each kernel to calculate the block specific short_id
-				let short_id = k.short_id(&hash, nonce);
+				let short_id = hasher.short_id(kernel_hash);
 				if kern_ids.contains(&short_id) {
-					txs.push(x.tx.clone());
+					txs.push(x.tx().clone());
 					found_ids.push(short_id);
 				}
 				if found_ids.len() == kern_ids.len() {
@@ -141,7 +145,7 @@ impl Pool {
 	}
 
 	pub fn all_transactions(&self) -> Vec<Transaction> {
-		self.entries.iter().map(|x| x.tx.clone()).collect()
+		self.entries.iter().map(|x| x.tx().clone()).collect()
 	}
 
 	/// Return a single aggregate tx representing all txs in the txpool.
@@ -173,7 +177,7 @@ impl Pool {
 		let mut txs = self.all_transactions();
 
 		// Quick check to see if we have seen this tx before.
-		if txs.contains(&entry.tx) {
+		if txs.contains(entry.tx()) {
 			return Err(PoolError::DuplicateTx);
 		}
 
@@ -181,11 +185,11 @@ impl Pool {
 
 		let agg_tx = if txs.is_empty() {
 			// If we have nothing to aggregate then simply return the tx itself.
-			entry.tx.clone()
+			entry.tx().clone()
 		} else {
 			// Create a single aggregated tx from the existing pool txs and the
 			// new entry
-			txs.push(entry.tx.clone());
+			txs.push(entry.tx().clone());
 			transaction::aggregate(txs)?
 		};
 
@@ -204,11 +208,11 @@ impl Pool {
 		debug!(
 			"add_to_pool [{}]: {} ({:?}) [in/out/kern: {}/{}/{}] pool: {} (at block {})",
 			self.name,
-			entry.tx.hash(),
+			entry.tx().hash(),
 			entry.src,
-			entry.tx.inputs().len(),
-			entry.tx.outputs().len(),
-			entry.tx.kernels().len(),
+			entry.tx().inputs().len(),
+			entry.tx().outputs().len(),
+			entry.tx().kernels().len(),
 			self.size(),
 			header.hash(),
 		);
@@ -267,7 +271,7 @@ impl Pool {
 		tx: &Transaction,
 		header: &BlockHeader,
 	) -> Result<BlockSums, PoolError> {
-		let overage = tx.overage();
+		let overage = tx.overage()?;
 		let offset = (header.total_kernel_offset() + tx.offset.clone())?;
 
 		let block_sums = self.blockchain.get_block_sums(&header.hash())?;
@@ -322,7 +326,7 @@ impl Pool {
 			let mut insert_pos = None;
 			let mut is_rejected = false;
 
-			for input in entry.tx.inputs() {
+			for input in entry.tx().inputs() {
 				if rejected.contains(&input.commitment()) {
 					// Depends on a rejected tx, so reject this one.
 					is_rejected = true;
@@ -341,7 +345,7 @@ impl Pool {
 
 			// If this tx is rejected then store all output commitments in our rejected set.
 			if is_rejected {
-				for out in entry.tx.outputs() {
+				for out in entry.tx().outputs() {
 					rejected.insert(out.commitment());
 				}
 
@@ -355,7 +359,7 @@ impl Pool {
 					// This is the common case for non 0-conf txs in the txpool.
 					// We assume the tx is valid here as we validated it on the way into the txpool.
 					insert_pos = Some(tx_buckets.len());
-					tx_buckets.push(Bucket::new(entry.tx.clone(), tx_buckets.len()));
+					tx_buckets.push(Bucket::new(entry.tx().clone(), tx_buckets.len()));
 				}
 				Some(pos) => {
 					// We found a single parent tx, so aggregate in the bucket
@@ -364,7 +368,7 @@ impl Pool {
 					let bucket = &tx_buckets[pos];
 
 					if let Ok(new_bucket) = bucket.aggregate_with_tx(
-						entry.tx.clone(),
+						entry.tx().clone(),
 						weighting,
 						self.verifier_cache.clone(),
 					) {
@@ -375,7 +379,7 @@ impl Pool {
 							// Otherwise put it in its own bucket at the end.
 							// Note: This bucket will have a lower fee_to_weight
 							// than the bucket it depends on.
-							tx_buckets.push(Bucket::new(entry.tx.clone(), tx_buckets.len()));
+							tx_buckets.push(Bucket::new(entry.tx().clone(), tx_buckets.len()));
 						}
 					} else {
 						// Aggregation failed so discard this new tx.
@@ -385,13 +389,13 @@ impl Pool {
 			}
 
 			if is_rejected {
-				for out in entry.tx.outputs() {
+				for out in entry.tx().outputs() {
 					rejected.insert(out.commitment());
 				}
 			} else if let Some(insert_pos) = insert_pos {
 				// We successfully added this tx to our set of buckets.
 				// Update commits index for subsequent txs.
-				for out in entry.tx.outputs() {
+				for out in entry.tx().outputs() {
 					output_commits.insert(out.commitment(), insert_pos);
 				}
 			}
@@ -416,9 +420,9 @@ impl Pool {
 
 		// Check each transaction in the pool
 		for entry in &self.entries {
-			let entry_kernel_set = entry.tx.kernels().iter().collect::<HashSet<_>>();
+			let entry_kernel_set = entry.tx().kernels().iter().collect::<HashSet<_>>();
 			if entry_kernel_set.is_subset(&kernel_set) {
-				found_txs.push(entry.tx.clone());
+				found_txs.push(entry.tx().clone());
 			}
 		}
 		found_txs
@@ -432,8 +436,8 @@ impl Pool {
 		// Also reject any txs where we see a conflicting tx,
 		// where an input is spent in a different tx.
 		self.entries.retain(|x| {
-			!x.tx.kernels().iter().any(|y| block.kernels().contains(y))
-				&& !x.tx.inputs().iter().any(|y| block.inputs().contains(y))
+			!x.tx().kernels().iter().any(|y| block.kernels().contains(y))
+				&& !x.tx().inputs().iter().any(|y| block.inputs().contains(y))
 		});
 	}
 
@@ -445,7 +449,7 @@ impl Pool {
 	/// Number of transaction kernels in the pool.
 	/// This may differ from the size (number of transactions) due to tx aggregation.
 	pub fn kernel_count(&self) -> usize {
-		self.entries.iter().map(|x| x.tx.kernels().len()).sum()
+		self.entries.iter().map(|x| x.tx().kernels().len()).sum()
 	}
 
 	/// Is the pool empty?
@@ -99,16 +99,16 @@ impl TransactionPool {
 		header: &BlockHeader,
 	) -> Result<(), PoolError> {
 		// First deaggregate the tx based on current txpool txs.
-		if entry.tx.kernels().len() > 1 {
-			let txs = self.txpool.find_matching_transactions(entry.tx.kernels());
+		if entry.tx().kernels().len() > 1 {
+			let txs = self.txpool.find_matching_transactions(entry.tx().kernels());
 			if !txs.is_empty() {
-				let tx = transaction::deaggregate(entry.tx, txs)?;
+				let tx_at = entry.tx_at;
+				let tx = transaction::deaggregate(entry.into_tx(), txs)?;
 
 				// Validate this deaggregated tx "as tx", subject to regular tx weight limits.
 				tx.validate(Weighting::AsTransaction, self.verifier_cache.clone())?;
 
-				entry.tx = tx;
-				entry.src = TxSource::Deaggregate;
+				entry = PoolEntry::new(TxSource::Deaggregate, tx_at, tx);
 			}
 		}
 		self.txpool.add_to_pool(entry.clone(), vec![], header)?;
@@ -157,11 +157,7 @@ impl TransactionPool {
 		// Check coinbase maturity before we go any further.
 		self.blockchain.verify_coinbase_maturity(&tx)?;
 
-		let entry = PoolEntry {
-			src,
-			tx_at: Utc::now(),
-			tx,
-		};
+		let entry = PoolEntry::new(src, Utc::now(), tx);
 
 		// If not stem then we are fluff.
 		// If this is a stem tx then attempt to stem.
@@ -196,7 +192,7 @@ impl TransactionPool {
 			// Remove transaction
 			self.txpool
 				.entries
-				.retain(|x| x.tx != *evictable_transaction);
+				.retain(|x| x.tx() != evictable_transaction);
 		};
 	}
 
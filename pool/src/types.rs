@@ -19,8 +19,8 @@ use chrono::prelude::{DateTime, Utc};
 
 use self::core::core::block;
 use self::core::core::committed;
-use self::core::core::hash::Hash;
-use self::core::core::transaction::{self, Transaction};
+use self::core::core::hash::{Hash, Hashed};
+use self::core::core::transaction::{self, Transaction, TxKernel};
 use self::core::core::{BlockHeader, BlockSums};
 use self::core::{consensus, global};
 use failure::Fail;
@@ -150,13 +150,76 @@ fn default_mineable_max_weight() -> usize {
 /// Represents a single entry in the pool.
 /// A single (possibly aggregated) transaction.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "PoolEntryData")]
 pub struct PoolEntry {
 	/// Info on where this tx originated from.
 	pub src: TxSource,
 	/// Timestamp of when this tx was originally added to the pool.
 	pub tx_at: DateTime<Utc>,
+	/// The transaction itself, only set via the constructor so the kernel
+	/// hashes below always match it.
+	tx: Transaction,
+	/// Hashes of the tx kernels, computed once when the entry is built so they
+	/// can be reused when deriving short_ids for each compact block we hydrate.
+	/// Bounded by the tx itself, and so by the pool size.
+	#[serde(skip)]
+	kernel_hashes: Vec<Hash>,
+}
+
+/// Serialized form of a pool entry, the kernel hashes are rebuilt on read.
+#[derive(Deserialize)]
+struct PoolEntryData {
+	src: TxSource,
+	tx_at: DateTime<Utc>,
+	tx: Transaction,
+}
+
+impl From<PoolEntryData> for PoolEntry {
+	fn from(data: PoolEntryData) -> PoolEntry {
+		PoolEntry::new(data.src, data.tx_at, data.tx)
+	}
+}
+
+impl PoolEntry {
+	/// Build a new pool entry for the tx, caching the tx kernel hashes.
+	pub fn new(src: TxSource, tx_at: DateTime<Utc>, tx: Transaction) -> PoolEntry {
+		PoolEntry::with_kernel_hasher(src, tx_at, tx, |k| k.hash())
+	}
+
+	/// Build a new pool entry for the tx, caching the tx kernel hashes as
+	/// computed by the provided fn (once per kernel).
+	pub fn with_kernel_hasher<F>(
+		src: TxSource,
+		tx_at: DateTime<Utc>,
+		tx: Transaction,
+		hash_kernel: F,
+	) -> PoolEntry
+	where
+		F: FnMut(&TxKernel) -> Hash,
+	{
+		let kernel_hashes = tx.kernels().iter().map(hash_kernel).collect();
+		PoolEntry {
+			src,
+			tx_at,
+			tx,
+			kernel_hashes,
+		}
+	}
+
 	/// The transaction itself.
-	pub tx: Transaction,
+	pub fn tx(&self) -> &Transaction {
+		&self.tx
+	}
+
+	/// Consume the entry, returning the transaction.
+	pub fn into_tx(self) -> Transaction {
+		self.tx
+	}
+
+	/// Hashes of the tx kernels (in kernel order).
+	pub fn kernel_hashes(&self) -> &[Hash] {
+		&self.kernel_hashes
+	}
 }
 
 /// Used to make decisions based on transaction acceptance priority from
@@ -205,10 +205,10 @@ fn test_transaction_pool_block_reconciliation() {
 			write_pool.reconcile_block(&block).unwrap();
 
 			assert_eq!(write_pool.total_size(), 4);
-			assert_eq!(write_pool.txpool.entries[0].tx, valid_transaction);
-			assert_eq!(write_pool.txpool.entries[1].tx, pool_child);
-			assert_eq!(write_pool.txpool.entries[2].tx, conflict_valid_child);
-			assert_eq!(write_pool.txpool.entries[3].tx, valid_child_valid);
+			assert_eq!(*write_pool.txpool.entries[0].tx(), valid_transaction);
+			assert_eq!(*write_pool.txpool.entries[1].tx(), pool_child);
+			assert_eq!(*write_pool.txpool.entries[2].tx(), conflict_valid_child);
+			assert_eq!(*write_pool.txpool.entries[3].tx(), valid_child_valid);
 		}
 	}
 	// Cleanup db directory
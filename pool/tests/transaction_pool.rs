@@ -14,18 +14,21 @@
 
 pub mod common;
 
+use self::core::core::hash::{Hash, Hashed};
 use self::core::core::verifier_cache::LruVerifierCache;
-use self::core::core::{transaction, Block, BlockHeader, Weighting};
+use self::core::core::{transaction, Block, BlockHeader, CompactBlock, Transaction, Weighting};
 use self::core::libtx;
 use self::core::pow::Difficulty;
 use self::keychain::{ExtKeychain, Keychain};
-use self::pool::TxSource;
+use self::pool::{PoolEntry, TxSource};
 use self::util::RwLock;
 use crate::common::*;
 use kepler_core as core;
 use kepler_keychain as keychain;
 use kepler_pool as pool;
 use kepler_util as util;
+use std::cell::Cell;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 /// Test we can add some txs to the pool (both stempool and txpool).
@@ -239,7 +242,7 @@ fn test_the_transaction_pool() {
 			.unwrap();
 		assert_eq!(write_pool.total_size(), 6);
 		let entry = write_pool.txpool.entries.last().unwrap();
-		assert_eq!(entry.tx.kernels().len(), 1);
+		assert_eq!(entry.tx().kernels().len(), 1);
 		assert_eq!(entry.src, TxSource::Deaggregate);
 	}
 
@@ -450,7 +453,7 @@ fn test_the_transaction_pool() {
 				.unwrap();
 			assert_eq!(write_pool.total_size(), 6);
 			let entry = write_pool.txpool.entries.last().unwrap();
-			assert_eq!(entry.tx.kernels().len(), 1);
+			assert_eq!(entry.tx().kernels().len(), 1);
 			assert_eq!(entry.src, TxSource::Deaggregate);
 		}
 
@@ -476,3 +479,177 @@ fn test_the_transaction_pool() {
 	// Cleanup db directory
 	clean_output_dir(db_root.clone());
 }
+
+/// Test hydrating different compact blocks sharing kernels reuses the kernel
+/// hashes cached on each pool entry.
+#[test]
+fn test_retrieve_transactions_reuses_kernel_hashes() {
+	let keychain: ExtKeychain = Keychain::from_random_seed(false).unwrap();
+
+	let db_root = ".kepler_retrieve_kernel_hashes".to_string();
+	clean_output_dir(db_root.clone());
+
+	let chain = Arc::new(ChainAdapter::init(db_root.clone()).unwrap());
+
+	let verifier_cache = Arc::new(RwLock::new(LruVerifierCache::new()));
+
+	let mut pool = test_setup(chain.clone(), verifier_cache.clone());
+
+	let header = {
+		let height = 1;
+		let key_id = ExtKeychain::derive_key_id(1, height as u32, 0, 0, 0);
+		let reward = libtx::reward::output(
+			&keychain,
+			&libtx::ProofBuilder::new(&keychain),
+			&key_id,
+			0,
+			height,
+			false,
+		)
+		.unwrap();
+		let block = Block::new(&BlockHeader::default(), vec![], Difficulty::min(), reward).unwrap();
+
+		chain.update_db_for_block(&block);
+
+		block.header
+	};
+
+	let initial_tx = test_transaction_spending_coinbase(&keychain, &header, vec![500, 600]);
+	let tx1 = test_transaction(&keychain, vec![500], vec![499]);
+	let tx2 = test_transaction(&keychain, vec![600], vec![599]);
+	for tx in vec![initial_tx, tx1.clone(), tx2.clone()] {
+		pool.add_to_pool(test_source(), tx, false, &header).unwrap();
+	}
+	assert_eq!(pool.total_size(), 3);
+
+	// Rebuild the entries with a kernel hasher counting its calls.
+	let hash_count = Cell::new(0);
+	let entries: Vec<PoolEntry> = pool
+		.txpool
+		.entries
+		.drain(..)
+		.map(|entry| {
+			PoolEntry::with_kernel_hasher(entry.src.clone(), entry.tx_at, entry.into_tx(), |k| {
+				hash_count.set(hash_count.get() + 1);
+				k.hash()
+			})
+		})
+		.collect();
+	pool.txpool.entries = entries;
+
+	// Kernel hashes are computed once per kernel, as each entry is built.
+	assert_eq!(hash_count.get(), 3);
+	for entry in &pool.txpool.entries {
+		let hashes: Vec<Hash> = entry.tx().kernels().iter().map(|k| k.hash()).collect();
+		assert_eq!(entry.kernel_hashes(), &hashes[..]);
+	}
+
+	let build_block = |txs: Vec<Transaction>, key_idx: u32| {
+		let key_id = ExtKeychain::derive_key_id(1, key_idx, 0, 0, 0);
+		let fee = txs.iter().map(|tx| tx.fee()).sum();
+		let reward = libtx::reward::output(
+			&keychain,
+			&libtx::ProofBuilder::new(&keychain),
+			&key_id,
+			fee,
+			2,
+			false,
+		)
+		.unwrap();
+		Block::new(&header, txs, Difficulty::min(), reward).unwrap()
+	};
+
+	// Two different compact blocks (different block hash and nonce) both
+	// including tx1, hydrated from the same cached kernel hashes.
+	let cb_a =
+		CompactBlock::from_block_with_nonce(build_block(vec![tx1.clone(), tx2.clone()], 2), 1);
+	let cb_b = CompactBlock::from_block_with_nonce(build_block(vec![tx1.clone()], 3), 2);
+	assert!(cb_a.hash() != cb_b.hash());
+
+	let (txs, missing) = pool.retrieve_transactions(cb_a.hash(), cb_a.nonce, cb_a.kern_ids());
+	assert_eq!(txs.len(), 2);
+	assert!(txs.contains(&tx1) && txs.contains(&tx2));
+	assert!(missing.is_empty());
+
+	let (txs, missing) = pool.retrieve_transactions(cb_b.hash(), cb_b.nonce, cb_b.kern_ids());
+	assert_eq!(txs, vec![tx1]);
+	assert!(missing.is_empty());
+
+	// No kernel was hashed again to hydrate either compact block.
+	assert_eq!(hash_count.get(), 3);
+
+	clean_output_dir(db_root.clone());
+}
+
+/// Test a compact block keeping tx outputs in full still hydrates from the pool.
+#[test]
+fn test_retrieve_transactions_keep_full() {
+	let keychain: ExtKeychain = Keychain::from_random_seed(false).unwrap();
+
+	let db_root = ".kepler_retrieve_keep_full".to_string();
+	clean_output_dir(db_root.clone());
+
+	let chain = Arc::new(ChainAdapter::init(db_root.clone()).unwrap());
+
+	let verifier_cache = Arc::new(RwLock::new(LruVerifierCache::new()));
+
+	let mut pool = test_setup(chain.clone(), verifier_cache.clone());
+
+	let header = {
+		let height = 1;
+		let key_id = ExtKeychain::derive_key_id(1, height as u32, 0, 0, 0);
+		let reward = libtx::reward::output(
+			&keychain,
+			&libtx::ProofBuilder::new(&keychain),
+			&key_id,
+			0,
+			height,
+			false,
+		)
+		.unwrap();
+		let block = Block::new(&BlockHeader::default(), vec![], Difficulty::min(), reward).unwrap();
+
+		chain.update_db_for_block(&block);
+
+		block.header
+	};
+
+	let initial_tx = test_transaction_spending_coinbase(&keychain, &header, vec![500, 600]);
+	let tx1 = test_transaction(&keychain, vec![500], vec![300, 199]);
+	for tx in vec![initial_tx, tx1.clone()] {
+		pool.add_to_pool(test_source(), tx, false, &header).unwrap();
+	}
+
+	let block = {
+		let key_id = ExtKeychain::derive_key_id(1, 2, 0, 0, 0);
+		let reward = libtx::reward::output(
+			&keychain,
+			&libtx::ProofBuilder::new(&keychain),
+			&key_id,
+			tx1.fee(),
+			2,
+			false,
+		)
+		.unwrap();
+		Block::new(&header, vec![tx1.clone()], Difficulty::min(), reward).unwrap()
+	};
+
+	// Keep one of the tx outputs in full, its kernel is still a short_id.
+	let keep_full: HashSet<_> = vec![tx1.outputs()[0].commitment()].into_iter().collect();
+	let cb = block.to_compact_block(&keep_full);
+	assert!(cb.out_full().contains(&tx1.outputs()[0]));
+	assert_eq!(cb.kern_ids().len(), 1);
+
+	let (txs, missing) = pool.retrieve_transactions(cb.hash(), cb.nonce, cb.kern_ids());
+	assert_eq!(txs, vec![tx1]);
+	assert!(missing.is_empty());
+
+	// The inputs come from the pool tx, so we get the full block back.
+	let hb = Block::hydrate_from(cb, txs).unwrap();
+	assert_eq!(hb.header, block.header);
+	assert_eq!(hb.inputs(), block.inputs());
+	assert_eq!(hb.outputs(), block.outputs());
+	assert_eq!(hb.kernels(), block.kernels());
+
+	clean_output_dir(db_root.clone());
+}
@@ -814,7 +814,7 @@ impl DandelionAdapter for PoolToNetAdapter {
 
 impl pool::PoolAdapter for PoolToNetAdapter {
 	fn tx_accepted(&self, entry: &pool::PoolEntry) {
-		self.peers().broadcast_transaction(&entry.tx);
+		self.peers().broadcast_transaction(entry.tx());
 	}
 
 	fn stem_tx_accepted(&self, entry: &pool::PoolEntry) -> Result<(), pool::PoolError> {
@@ -828,7 +828,7 @@ impl pool::PoolAdapter for PoolToNetAdapter {
 		// If node is configured to always stem our (pushed via api) txs then do so.
 		if epoch.is_stem() || (entry.src.is_pushed() && epoch.always_stem_our_txs()) {
 			if let Some(peer) = epoch.relay_peer(&self.peers()) {
-				match peer.send_stem_transaction(&entry.tx) {
+				match peer.send_stem_transaction(entry.tx()) {
 					Ok(_) => {
 						info!("Stemming this epoch, relaying to next peer.");
 						Ok(())
@@ -127,7 +127,7 @@ fn process_fluff_phase(
 
 	let fluffable_txs = {
 		let txpool_tx = tx_pool.txpool.all_transactions_aggregate()?;
-		let txs: Vec<_> = all_entries.into_iter().map(|x| x.tx).collect();
+		let txs: Vec<_> = all_entries.into_iter().map(PoolEntry::into_tx).collect();
 		tx_pool.stempool.validate_raw_txs(
 			&txs,
 			txpool_tx,
@@ -170,8 +170,8 @@ fn process_expired_entries(
 	let header = tx_pool.chain_head()?;
 
 	for entry in expired_entries {
-		let txhash = entry.tx.hash();
-		match tx_pool.add_to_pool(TxSource::EmbargoExpired, entry.tx, false, &header) {
+		let txhash = entry.tx().hash();
+		match tx_pool.add_to_pool(TxSource::EmbargoExpired, entry.into_tx(), false, &header) {
 			Ok(_) => info!(
 				"dand_mon: embargo expired for {}, fluffed successfully.",
 				txhash
@@ -97,6 +97,7 @@ impl Miner {
 			latest_hash
 		);
 		let mut iter_count = 0;
+		let mut pre_pow = b.header.pre_pow_template();
 
 		while head.hash() == *latest_hash && Utc::now().timestamp() < deadline {
 			let mut ctx = global::create_pow_context::<u32>(
@@ -106,8 +107,8 @@ impl Miner {
 				10,
 			)
 			.unwrap();
-			ctx.set_header_nonce(b.header.pre_pow(), None, true)
-				.unwrap();
+			BlockHeader::splice_nonce(&mut pre_pow, b.header.pow.nonce);
+			ctx.set_header_nonce(pre_pow.clone(), None, true).unwrap();
 			if let Ok(proofs) = ctx.find_cycles() {
 				b.header.pow.proof = proofs[0].clone();
 				let proof_diff = b.header.pow.to_difficulty(b.header.height);